@@ -0,0 +1,171 @@
+//! Recording of draw operations to apply to an `FBmanager` later
+
+use crate::{FBmanager, FBmanagerError, Image, Point, Window, WindowId, WindowStore};
+#[cfg(feature = "ttf")]
+use crate::Font;
+use std::fmt;
+use std::sync::Arc;
+
+/// A draw operation on a `Window` of an `FBmanager`. Points are window-relative
+///
+/// Images and fonts are shared through `Arc`s, so cloning an operation is cheap
+#[derive(Clone)]
+pub enum DrawOp {
+	/// Fills the `Window` like `FBmanager::fill`
+	Fill {id: WindowId, rgb: (u8,u8,u8)},
+	/// Draws a line like `Window::draw_line`
+	Line {id: WindowId, from: Point, to: Point, rgb: (u8,u8,u8)},
+	/// Draws an `Image` like `Window::draw_image`
+	Blit {id: WindowId, origin: Point, image: Arc<Image>},
+	/// Draws text like `Window::draw_text`
+	#[cfg(feature = "ttf")]
+	Text {id: WindowId, origin: Point, text: String, font: Arc<Font>, size: f32, rgb: (u8,u8,u8)},
+}
+
+impl DrawOp {
+	/// Gets the id of the `Window` the operation draws to
+	pub fn id(&self) -> WindowId {
+		match self {
+			DrawOp::Fill {id, ..} | DrawOp::Line {id, ..} | DrawOp::Blit {id, ..} => *id,
+			#[cfg(feature = "ttf")]
+			DrawOp::Text {id, ..} => *id,
+		}
+	}
+	/// Draws the operation into `window` in `buffer`. Fills overwrite, without the global alpha of an `FBmanager`
+	fn draw(&self, window: &Window, buffer: &mut [u8]) {
+		match self {
+			DrawOp::Fill {rgb, ..} => window.fill(buffer, *rgb, None),
+			DrawOp::Line {from, to, rgb, ..} => window.draw_line(buffer, *from, *to, *rgb),
+			DrawOp::Blit {origin, image, ..} => window.draw_image(buffer, *origin, image),
+			#[cfg(feature = "ttf")]
+			DrawOp::Text {origin, text, font, size, rgb, ..} => window.draw_text(buffer, *origin, text, font, *size, *rgb),
+		}
+	}
+}
+
+impl fmt::Debug for DrawOp {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			DrawOp::Fill {id, rgb} => f.debug_struct("Fill").field("id", id).field("rgb", rgb).finish(),
+			DrawOp::Line {id, from, to, rgb} => {
+				f.debug_struct("Line").field("id", id).field("from", from).field("to", to).field("rgb", rgb).finish()
+			},
+			DrawOp::Blit {id, origin, image} => {
+				let size = (image.width, image.height);
+				f.debug_struct("Blit").field("id", id).field("origin", origin).field("image_size", &size).finish()
+			},
+			// fonts aren't `Debug`, so only their name is shown
+			#[cfg(feature = "ttf")]
+			DrawOp::Text {id, origin, text, font, size, rgb} => {
+				f.debug_struct("Text").field("id", id).field("origin", origin).field("text", text)
+					.field("font", &font.name()).field("size", size).field("rgb", rgb).finish()
+			},
+		}
+	}
+}
+
+/// A list of `DrawOp`s, applied in order by `FBmanager::execute` or `replay`
+#[derive(Clone,Debug,Default)]
+pub struct DisplayList {
+	ops: Vec<DrawOp>,
+}
+
+impl DisplayList {
+	/// Creates a new empty `DisplayList`
+	pub fn new() -> Self {
+		DisplayList {ops: Vec::new()}
+	}
+	/// Appends `op` to the end of the list
+	pub fn push(&mut self, op: DrawOp) {
+		self.ops.push(op);
+	}
+	/// Gets the recorded operations in order
+	pub fn ops(&self) -> &[DrawOp] {
+		&self.ops
+	}
+	/// Gets the number of recorded operations
+	pub fn len(&self) -> usize {
+		self.ops.len()
+	}
+	/// Checks if no operations are recorded
+	pub fn is_empty(&self) -> bool {
+		self.ops.is_empty()
+	}
+	/// Drops all recorded operations
+	pub fn clear(&mut self) {
+		self.ops.clear();
+	}
+	/// Checks that every operation's `Window` exists in `windows`
+	fn check(&self, windows: &WindowStore) -> Result<(), FBmanagerError> {
+		if self.ops.iter().all(|op| windows.contains(op.id())) {
+			Ok(())
+		} else {
+			Err(FBmanagerError::InvalidWindow)
+		}
+	}
+	/// Applies the operations in order to `buffer`, e.g. a buffer from `FBmanager::render_target`
+	///
+	/// Returns `FBmanagerError::InvalidWindow` without drawing anything if an operation's `Window` doesn't exist
+	pub fn replay(&self, windows: &WindowStore, buffer: &mut [u8]) -> Result<(), FBmanagerError> {
+		self.check(windows)?;
+		for op in &self.ops {
+			op.draw(&windows[op.id()], buffer);
+		}
+		Ok(())
+	}
+}
+
+impl FBmanager {
+	/// Applies the operations of `list` to the buffer in order
+	///
+	/// Operations on frozen `Window`s are skipped, every changed `Window` is marked dirty and the global alpha applies to all operations.
+	/// Returns `FBmanagerError::InvalidWindow` without drawing anything if an operation's `Window` doesn't exist
+	pub fn execute(&mut self, list: &DisplayList) -> Result<(), FBmanagerError> {
+		list.check(&self.windows)?;
+		for op in list.ops() {
+			let id = op.id();
+			if let DrawOp::Fill {rgb, ..} = op {
+				// goes through `fill` to keep the fill color and global alpha behaviour
+				self.fill(id, *rgb);
+				continue;
+			}
+			if self.is_window_frozen(id) {
+				continue;
+			}
+			self.draw_blended(id, |window, buffer| op.draw(window, buffer));
+			self.mark_dirty(self.windows[id].main_context.rect());
+		}
+		Ok(())
+	}
+}
+
+//tests
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::tests::test_window;
+
+	#[test]
+	fn record_and_replay() {
+		let (window, mut buffer) = test_window(4, 4, 4);
+		let mut windows = WindowStore::default();
+		let id = windows.insert(window);
+		let mut list = DisplayList::new();
+		list.push(DrawOp::Fill {id, rgb: (1,2,3)});
+		list.push(DrawOp::Line {id, from: Point::new(0,0), to: Point::new(3,0), rgb: (9,9,9)});
+		let img = Arc::new(Image::from_rgb(1, 1, &[5,5,5]).unwrap());
+		list.push(DrawOp::Blit {id, origin: Point::new(2,2), image: img});
+		let copy = list.clone();
+		assert_eq!(copy.len(), 3);
+		copy.replay(&windows, &mut buffer).unwrap();
+		let rows = &windows[id].main_context.pixels;
+		assert_eq!(rows[0][3].get_rgb(&buffer), (9,9,9));
+		assert_eq!(rows[1][1].get_rgb(&buffer), (1,2,3));
+		assert_eq!(rows[2][2].get_rgb(&buffer), (5,5,5));
+		windows.remove(id);
+		let mut untouched = vec![0u8; 64];
+		assert!(list.replay(&windows, &mut untouched).is_err());
+		assert!(untouched.iter().all(|&b| b == 0));
+	}
+}
@@ -0,0 +1,234 @@
+use crate::{blend, FBmanagerError, Pixel, Point, Rect, Window};
+
+/// An RGBA image stored row-major with 4 bytes per pixel
+#[derive(Clone,Debug)]
+pub struct Image {
+	pub width: usize,
+	pub height: usize,
+	pub data: Vec<u8>,
+}
+
+impl Image {
+	/// Creates a new transparent `Image` of the given size
+	pub fn new(width: usize, height: usize) -> Self {
+		Image {width, height, data: vec![0u8; width * height * 4]}
+	}
+	/// Creates an `Image` from RGBA bytes. Returns `None` if `data` isn't `width * height * 4` bytes long
+	pub fn from_rgba(width: usize, height: usize, data: Vec<u8>) -> Option<Self> {
+		if data.len() != width * height * 4 {
+			return None;
+		}
+		Some(Image {width, height, data})
+	}
+	/// Creates an opaque `Image` from RGB bytes. Returns `None` if `data` isn't `width * height * 3` bytes long
+	pub fn from_rgb(width: usize, height: usize, data: &[u8]) -> Option<Self> {
+		if data.len() != width * height * 3 {
+			return None;
+		}
+		let mut rgba = Vec::with_capacity(width * height * 4);
+		for p in data.chunks(3) {
+			rgba.extend_from_slice(&[p[0], p[1], p[2], 255]);
+		}
+		Some(Image {width, height, data: rgba})
+	}
+	/// Gets the RGBA value at `(x, y)`. Panics if it is outside of the `Image`
+	pub fn get_rgba(&self, x: usize, y: usize) -> (u8,u8,u8,u8) {
+		assert!(x < self.width && y < self.height, "pixel is outside of the image");
+		let i = (y * self.width + x) * 4;
+		(self.data[i], self.data[i+1], self.data[i+2], self.data[i+3])
+	}
+	/// Sets the RGBA value at `(x, y)`. Panics if it is outside of the `Image`
+	pub fn set_rgba(&mut self, x: usize, y: usize, rgba: (u8,u8,u8,u8)) {
+		assert!(x < self.width && y < self.height, "pixel is outside of the image");
+		let i = (y * self.width + x) * 4;
+		self.data[i..i+4].copy_from_slice(&[rgba.0, rgba.1, rgba.2, rgba.3]);
+	}
+}
+
+impl Window {
+	/// Draws `img` with its top left corner at the window-relative `origin`, blending by its alpha
+	///
+	/// The part of the `Image` outside of the `main_context` is clipped
+	pub fn draw_image(&self, buffer: &mut [u8], origin: Point, img: &Image) {
+		let src_rect = Rect::new(0, 0, img.width, img.height);
+		// the whole image is always a valid region
+		let _ = self.draw_image_region(buffer, origin, img, src_rect);
+	}
+	/// Draws the `src_rect` region of `img` with its top left corner at the window-relative `dst_origin`, blending by its alpha
+	///
+	/// Returns `FBmanagerError::OutOfBounds` if `src_rect` isn't inside of the `Image`. The part outside of the `main_context` is clipped
+	pub fn draw_image_region(&self, buffer: &mut [u8], dst_origin: Point, img: &Image, src_rect: Rect) -> Result<(), FBmanagerError> {
+		if src_rect.x + src_rect.width > img.width || src_rect.y + src_rect.height > img.height {
+			return Err(FBmanagerError::OutOfBounds);
+		}
+		let width = src_rect.width.min(self.main_context.width.saturating_sub(dst_origin.x));
+		let height = src_rect.height.min(self.main_context.height.saturating_sub(dst_origin.y));
+		for y in 0..height {
+			let row = &self.main_context.pixels[dst_origin.y + y];
+			for x in 0..width {
+				let rgba = img.get_rgba(src_rect.x + x, src_rect.y + y);
+				put_rgba(&row[dst_origin.x + x], buffer, rgba);
+			}
+		}
+		Ok(())
+	}
+	/// Draws `img` scaled up by a whole `factor` with its top left corner at the window-relative `origin`, blending by its alpha
+	///
+	/// Each source pixel becomes a `factor` × `factor` block, so the result stays crisp. A `factor` of 1 is the same as `draw_image`
+	/// and 0 draws nothing. The part outside of the `main_context` is clipped
+	pub fn draw_image_integer_scaled(&self, buffer: &mut [u8], origin: Point, img: &Image, factor: usize) {
+		let width = (img.width * factor).min(self.main_context.width.saturating_sub(origin.x));
+		let height = (img.height * factor).min(self.main_context.height.saturating_sub(origin.y));
+		for y in 0..height {
+			let row = &self.main_context.pixels[origin.y + y];
+			for x in 0..width {
+				put_rgba(&row[origin.x + x], buffer, img.get_rgba(x / factor, y / factor));
+			}
+		}
+	}
+	/// Fills the `main_context` with a vertical gradient from `top_rgb` to `bottom_rgb` with `logo` centered on it, blended by its alpha
+	///
+	/// Every `Pixel` is written once, so the splash never shows half drawn. A `logo` larger than the `main_context` is clipped
+	pub fn draw_splash(&self, buffer: &mut [u8], top_rgb: (u8,u8,u8), bottom_rgb: (u8,u8,u8), logo: &Image) {
+		let origin = self.center_rect(logo.width, logo.height);
+		let logo_rect = Rect::new(origin.x, origin.y, logo.width, logo.height);
+		let height = self.main_context.height;
+		for (y, row) in self.main_context.pixels.iter().enumerate() {
+			let t = if height > 1 { y as f32 / (height - 1) as f32 } else { 0.0 };
+			let bg = blend(top_rgb, bottom_rgb, t);
+			for (x, p) in row.iter().enumerate() {
+				let (r, g, b) = if logo_rect.contains(&Rect::new(x, y, 1, 1)) {
+					let (r, g, b, a) = logo.get_rgba(x - origin.x, y - origin.y);
+					blend(bg, (r, g, b), a as f32 / 255.0)
+				} else {
+					bg
+				};
+				p.set_rgb(buffer, r, g, b);
+			}
+		}
+	}
+	/// Draws `img` rotated clockwise by `angle_deg` about its center, placed at the window-relative `center`
+	///
+	/// Uses nearest-neighbor sampling. The part outside of the `main_context` is clipped
+	pub fn draw_image_rotated(&self, buffer: &mut [u8], center: Point, img: &Image, angle_deg: f32) {
+		let (sin, cos) = angle_deg.to_radians().sin_cos();
+		let half_w = img.width as f32 / 2.0;
+		let half_h = img.height as f32 / 2.0;
+		// every rotation of the image fits in a circle of its half diagonal
+		let radius = (half_w * half_w + half_h * half_h).sqrt().ceil() as usize;
+		let x_start = center.x.saturating_sub(radius);
+		let x_end = center.x + radius + 1;
+		let y_end = (center.y + radius + 1).min(self.main_context.height);
+		for y in center.y.saturating_sub(radius)..y_end {
+			let dy = y as f32 + 0.5 - center.y as f32;
+			let row = &self.main_context.pixels[y];
+			for (x, p) in row.iter().enumerate().take(x_end).skip(x_start) {
+				let dx = x as f32 + 0.5 - center.x as f32;
+				// inverse rotation back into the source image
+				let sx = (dx * cos + dy * sin + half_w).floor();
+				let sy = (dy * cos - dx * sin + half_h).floor();
+				if sx < 0.0 || sy < 0.0 || sx >= img.width as f32 || sy >= img.height as f32 {
+					continue;
+				}
+				put_rgba(p, buffer, img.get_rgba(sx as usize, sy as usize));
+			}
+		}
+	}
+	/// Draws `img` with its top left corner at the window-relative `origin`, quantized to `channel_bits` of red, green and blue with Floyd–Steinberg dithering
+	///
+	/// Use `FBmanager::channel_bits` for the device's depth. The `Image` is blended by its alpha before quantizing.
+	/// The part outside of the `main_context` is clipped
+	pub fn draw_image_dithered(&self, buffer: &mut [u8], origin: Point, img: &Image, channel_bits: (u8,u8,u8)) {
+		let width = img.width.min(self.main_context.width.saturating_sub(origin.x));
+		let height = img.height.min(self.main_context.height.saturating_sub(origin.y));
+		let bits = [channel_bits.0, channel_bits.1, channel_bits.2];
+		let quantize = |v: f32, bits: u8| {
+			if bits >= 8 {
+				return v.round().clamp(0.0, 255.0);
+			}
+			let levels = ((1u32 << bits) - 1) as f32;
+			((v / 255.0 * levels).round().clamp(0.0, levels) / levels * 255.0).round()
+		};
+		// accumulated error of the current and next row, with a column of padding on each side
+		let mut error = vec![[0f32; 3]; width + 2];
+		let mut next_error = vec![[0f32; 3]; width + 2];
+		for y in 0..height {
+			let row = &self.main_context.pixels[origin.y + y];
+			for x in 0..width {
+				let p = &row[origin.x + x];
+				let (r, g, b, a) = img.get_rgba(x, y);
+				let (r, g, b) = blend(p.get_rgb(buffer), (r, g, b), a as f32 / 255.0);
+				let mut out = [0u8; 3];
+				for (c, v) in [r, g, b].iter().enumerate() {
+					let wanted = *v as f32 + error[x + 1][c];
+					let q = quantize(wanted, bits[c]);
+					out[c] = q as u8;
+					let e = wanted - q;
+					error[x + 2][c] += e * 7.0 / 16.0;
+					next_error[x][c] += e * 3.0 / 16.0;
+					next_error[x + 1][c] += e * 5.0 / 16.0;
+					next_error[x + 2][c] += e / 16.0;
+				}
+				p.set_rgb(buffer, out[0], out[1], out[2]);
+			}
+			std::mem::swap(&mut error, &mut next_error);
+			next_error.iter_mut().for_each(|e| *e = [0.0; 3]);
+		}
+	}
+}
+
+/// Writes an RGBA value to a `Pixel`, blending by its alpha
+pub(crate) fn put_rgba(p: &Pixel, buffer: &mut [u8], rgba: (u8,u8,u8,u8)) {
+	match rgba.3 {
+		0 => {},
+		255 => p.set_rgb(buffer, rgba.0, rgba.1, rgba.2),
+		a => p.blend_rgb(buffer, rgba.0, rgba.1, rgba.2, a as f32 / 255.0),
+	}
+}
+
+//tests
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::tests::test_window;
+
+	#[test]
+	fn rotate_quarter_turn() {
+		let (window, mut buffer) = test_window(4, 4, 4);
+		let img = Image::from_rgb(2, 2, &[1,1,1, 2,2,2, 3,3,3, 4,4,4]).unwrap();
+		window.draw_image_rotated(&mut buffer, Point::new(2,2), &img, 90.0);
+		let rows = &window.main_context.pixels;
+		assert_eq!(rows[1][1].get_rgb(&buffer), (3,3,3));
+		assert_eq!(rows[1][2].get_rgb(&buffer), (1,1,1));
+		assert_eq!(rows[2][1].get_rgb(&buffer), (4,4,4));
+		assert_eq!(rows[2][2].get_rgb(&buffer), (2,2,2));
+		assert_eq!(rows[0][0].get_rgb(&buffer), (0,0,0));
+	}
+
+	#[test]
+	fn integer_scale_clips() {
+		let (window, mut buffer) = test_window(4, 4, 4);
+		let img = Image::from_rgb(2, 2, &[1,1,1, 2,2,2, 3,3,3, 4,4,4]).unwrap();
+		window.draw_image_integer_scaled(&mut buffer, Point::new(1,1), &img, 2);
+		let rows = &window.main_context.pixels;
+		assert_eq!(rows[0][0].get_rgb(&buffer), (0,0,0));
+		assert_eq!(rows[1][1].get_rgb(&buffer), (1,1,1));
+		assert_eq!(rows[2][2].get_rgb(&buffer), (1,1,1));
+		assert_eq!(rows[1][3].get_rgb(&buffer), (2,2,2));
+		assert_eq!(rows[3][3].get_rgb(&buffer), (4,4,4));
+	}
+
+	#[test]
+	fn dither_to_channel_bits() {
+		let (window, mut buffer) = test_window(4, 1, 4);
+		let img = Image::from_rgb(4, 1, &[128; 12]).unwrap();
+		window.draw_image_dithered(&mut buffer, Point::new(0,0), &img, (1,1,1));
+		let rows = &window.main_context.pixels;
+		let reds: Vec<_> = rows[0].iter().map(|p| p.get_rgb(&buffer).0).collect();
+		assert!(reds.iter().all(|&r| r == 0 || r == 255));
+		assert!(reds.contains(&0) && reds.contains(&255));
+		window.draw_image_dithered(&mut buffer, Point::new(0,0), &img, (8,8,8));
+		assert!(rows[0].iter().all(|p| p.get_rgb(&buffer) == (128,128,128)));
+	}
+}
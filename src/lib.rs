@@ -1,8 +1,91 @@
 use framebuffer::Framebuffer;
 use framebuffer::KdMode;
 use framebuffer::FramebufferError;
+use framebuffer::VarScreeninfo;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::io::Write;
+use std::mem::size_of;
 use std::ops::Add;
 use std::ops::AddAssign;
+use std::ops::Index;
+use std::ops::IndexMut;
+use std::os::unix::fs::FileExt;
+use std::path::Path;
+use std::time::Duration;
+use std::time::Instant;
+
+mod image;
+pub use image::Image;
+
+mod display_list;
+pub use display_list::{DisplayList, DrawOp};
+
+#[cfg(feature = "ttf")]
+mod text;
+#[cfg(feature = "ttf")]
+pub use text::*;
+
+#[cfg(feature = "qr")]
+mod qr;
+
+#[cfg(feature = "png")]
+mod screenshot;
+
+#[cfg(feature = "remote")]
+mod remote;
+#[cfg(feature = "remote")]
+pub use remote::RemoteReceiver;
+
+/// Errors that can occur while managing `Window`s
+#[derive(Debug)]
+pub enum FBmanagerError {
+	/// A `WindowTemplate` or region does not fit in the area it is placed in
+	OutOfBounds,
+	/// No `Window` exists with the given id
+	InvalidWindow,
+	/// The size of the given data doesn't match the area it is drawn to
+	SizeMismatch,
+	/// Two channels of a `PixelFormat` share a byte offset
+	InvalidPixelFormat,
+	/// The framebuffer device couldn't be opened
+	Framebuffer(FramebufferError),
+	/// The framebuffer isn't laid out in linear scanlines. Holds the `fb_type` the driver reports
+	UnsupportedLayout(u32),
+}
+
+impl Error for FBmanagerError {
+	fn source(&self) -> Option<&(dyn Error + 'static)> {
+		match self {
+			FBmanagerError::Framebuffer(e) => Some(e),
+			_ => None,
+		}
+	}
+}
+
+impl From<FramebufferError> for FBmanagerError {
+	fn from(e: FramebufferError) -> Self {
+		FBmanagerError::Framebuffer(e)
+	}
+}
+
+impl fmt::Display for FBmanagerError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			FBmanagerError::OutOfBounds => write!(f, "region is out of bounds"),
+			FBmanagerError::InvalidWindow => write!(f, "no window exists with the given id"),
+			FBmanagerError::SizeMismatch => write!(f, "data size doesn't match the drawing area"),
+			FBmanagerError::InvalidPixelFormat => write!(f, "pixel format channels overlap"),
+			FBmanagerError::Framebuffer(e) => write!(f, "couldn't open the framebuffer: {}", e),
+			FBmanagerError::UnsupportedLayout(t) => write!(f, "framebuffer type {} isn't packed pixels", t),
+		}
+	}
+}
 
 /// Represents a pixel on the screen
 pub struct Pixel {
@@ -20,10 +103,114 @@ impl Pixel {
 	pub fn get_rgb(&self, buffer: &[u8]) -> (u8,u8,u8) {
 		(buffer[self.index+2], buffer[self.index+1], buffer[self.index])
 	}
+	/// Blends the given RGB value over the `Pixel`'s current color. `alpha` ranges from 0.0 (unchanged) to 1.0 (opaque)
+	pub fn blend_rgb(&self, buffer: &mut [u8], r: u8, g: u8, b: u8, alpha: f32) {
+		let (cr, cg, cb) = self.get_rgb(buffer);
+		let (r, g, b) = blend((cr,cg,cb), (r,g,b), alpha);
+		self.set_rgb(buffer, r, g, b);
+	}
+}
+
+/// The byte offset of each color channel within a pixel of the device
+///
+/// `Pixel`s are always stored in the internal buffer as BGR. They are reordered into the `PixelFormat` when drawn to the screen
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub struct PixelFormat {
+	pub red: usize,
+	pub green: usize,
+	pub blue: usize,
+}
+
+impl PixelFormat {
+	/// The channel order of the internal buffer
+	pub const BGR: PixelFormat = PixelFormat {red: 2, green: 1, blue: 0};
+	/// Red first, as used by some drivers
+	pub const RGB: PixelFormat = PixelFormat {red: 0, green: 1, blue: 2};
+
+	/// Checks that every channel offset fits in a pixel of `bytespp` bytes and that no two channels share one
+	fn check(&self, bytespp: usize) -> Result<(), FBmanagerError> {
+		if self.red >= bytespp || self.green >= bytespp || self.blue >= bytespp {
+			return Err(FBmanagerError::OutOfBounds);
+		}
+		if self.red == self.green || self.red == self.blue || self.green == self.blue {
+			return Err(FBmanagerError::InvalidPixelFormat);
+		}
+		Ok(())
+	}
+	/// Detects the `PixelFormat` from the channel offsets reported by the device, falling back to `BGR` if they aren't byte aligned, don't fit in a pixel or overlap
+	pub fn detect(info: &VarScreeninfo) -> Self {
+		let offsets = [info.red.offset, info.green.offset, info.blue.offset];
+		if offsets.iter().any(|o| o % 8 != 0) || (info.red.length == 0 && info.green.length == 0 && info.blue.length == 0) {
+			return PixelFormat::BGR;
+		}
+		let format = PixelFormat {
+			red: (info.red.offset / 8) as usize,
+			green: (info.green.offset / 8) as usize,
+			blue: (info.blue.offset / 8) as usize,
+		};
+		match format.check((info.bits_per_pixel / 8) as usize) {
+			Ok(()) => format,
+			Err(_) => PixelFormat::BGR,
+		}
+	}
+}
+
+/// Linearly blends `src` over `dst` by `alpha`, clamped to 0.0..=1.0
+fn blend(dst: (u8,u8,u8), src: (u8,u8,u8), alpha: f32) -> (u8,u8,u8) {
+	let alpha = alpha.clamp(0.0, 1.0);
+	let mix = |d: u8, s: u8| (d as f32 + (s as f32 - d as f32) * alpha).round() as u8;
+	(mix(dst.0, src.0), mix(dst.1, src.1), mix(dst.2, src.2))
+}
+
+/// Computes the perceived brightness of a color with the Rec. 601 luma weights
+fn luma(rgb: (u8,u8,u8)) -> u8 {
+	let y = 0.299 * rgb.0 as f32 + 0.587 * rgb.1 as f32 + 0.114 * rgb.2 as f32;
+	y.round().clamp(0.0, 255.0) as u8
+}
+
+/// Hashes a lattice point to a value in 0.0..1.0
+fn lattice_value(seed: u64, x: i64, y: i64) -> f32 {
+	// splitmix64 finalizer over the combined coordinates
+	let mut h = seed ^ (x as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15) ^ (y as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F);
+	h = (h ^ (h >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+	h = (h ^ (h >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+	h ^= h >> 31;
+	(h >> 40) as f32 / (1u64 << 24) as f32
+}
+
+/// Smoothly interpolates the lattice values around `(x, y)`
+fn value_noise(seed: u64, x: f32, y: f32) -> f32 {
+	let (x0, y0) = (x.floor(), y.floor());
+	let smooth = |t: f32| t * t * (3.0 - 2.0 * t);
+	let (tx, ty) = (smooth(x - x0), smooth(y - y0));
+	let (ix, iy) = (x0 as i64, y0 as i64);
+	let top = lattice_value(seed, ix, iy) * (1.0 - tx) + lattice_value(seed, ix + 1, iy) * tx;
+	let bottom = lattice_value(seed, ix, iy + 1) * (1.0 - tx) + lattice_value(seed, ix + 1, iy + 1) * tx;
+	top * (1.0 - ty) + bottom * ty
+}
+
+/// Picks black or white, whichever contrasts more with `bg` by relative luminance
+///
+/// #Examples
+///
+/// ```
+/// use framebuffer_manager::contrasting_color;
+///
+/// assert_eq!(contrasting_color((255,255,0)), (0,0,0));
+/// assert_eq!(contrasting_color((0,0,128)), (255,255,255));
+/// ```
+pub fn contrasting_color(bg: (u8,u8,u8)) -> (u8,u8,u8) {
+	let linear = |c: u8| {
+		let c = c as f32 / 255.0;
+		if c <= 0.03928 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+	};
+	let luminance = 0.2126 * linear(bg.0) + 0.7152 * linear(bg.1) + 0.0722 * linear(bg.2);
+	// where the contrast ratios against black and white are equal
+	if luminance > 0.179 { (0,0,0) } else { (255,255,255) }
 }
 
 /// Represents a point
-#[derive(Clone,Copy)]
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
 pub struct Point {
 	pub x: usize,
 	pub y: usize,
@@ -72,6 +259,70 @@ impl From<(usize,usize)> for Point {
 	}
 }
 
+/// Represents a rectangular area without any `Pixel`s
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub struct Rect {
+	pub x: usize,
+	pub y: usize,
+	pub width: usize,
+	pub height: usize,
+}
+
+impl Rect {
+	/// Creates a new `Rect` with its top left corner at x, y and the given size
+	pub fn new(x: usize, y: usize, width: usize, height: usize) -> Self {
+		Rect{x, y, width, height}
+	}
+	/// Gets the smallest `Rect` containing both `Rect`s
+	pub fn union(&self, other: &Rect) -> Rect {
+		let x = self.x.min(other.x);
+		let y = self.y.min(other.y);
+		let x_end = (self.x + self.width).max(other.x + other.width);
+		let y_end = (self.y + self.height).max(other.y + other.height);
+		Rect::new(x, y, x_end - x, y_end - y)
+	}
+	/// Gets the overlap of both `Rect`s, which has no area if they don't overlap
+	pub fn intersection(&self, other: &Rect) -> Rect {
+		let x = self.x.max(other.x);
+		let y = self.y.max(other.y);
+		let x_end = (self.x + self.width).min(other.x + other.width).max(x);
+		let y_end = (self.y + self.height).min(other.y + other.height).max(y);
+		Rect::new(x, y, x_end - x, y_end - y)
+	}
+	/// Checks if both `Rect`s share any area
+	pub fn overlaps(&self, other: &Rect) -> bool {
+		let overlap = self.intersection(other);
+		overlap.width > 0 && overlap.height > 0
+	}
+	/// Checks if `other` lies completely inside of the `Rect`
+	pub fn contains(&self, other: &Rect) -> bool {
+		other.x >= self.x && other.y >= self.y
+			&& other.x + other.width <= self.x + self.width
+			&& other.y + other.height <= self.y + self.height
+	}
+}
+
+/// Maps screen coordinates to byte offsets in a buffer
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+struct Layout {
+	/// The screen position of the buffer's first `Pixel`
+	origin: Point,
+	/// The length of a line of the buffer in bytes
+	stride: usize,
+	bytespp: usize,
+}
+
+impl Layout {
+	/// Creates a `Layout` for a buffer starting at the top left of the screen
+	fn new(stride: usize, bytespp: usize) -> Self {
+		Layout {origin: Point::new(0,0), stride, bytespp}
+	}
+	/// Gets the byte offset of the screen position `(x, y)`
+	fn index(&self, x: usize, y: usize) -> usize {
+		(y - self.origin.y) * self.stride + (x - self.origin.x) * self.bytespp
+	}
+}
+
 pub struct Rectangle {
 	pub location: Point,
 	pub height: usize,
@@ -80,15 +331,13 @@ pub struct Rectangle {
 }
 
 impl Rectangle {
-	/// Creates a new `Rectangle` from the given dimensions and assigns the `Pixel`s their proper indicies based on the given `Framebuffer`
-	fn from_dimensions(loc: &Point, height: usize, width: usize, fb : &Framebuffer) -> Self {
-		let line_length = fb.fix_screen_info.line_length as usize;
-		let bytespp = (fb.var_screen_info.bits_per_pixel / 8) as usize;
+	/// Creates a new `Rectangle` from the given dimensions and assigns the `Pixel`s their indices in a buffer with the given `Layout`
+	fn from_geometry(loc: &Point, height: usize, width: usize, layout: &Layout) -> Self {
 		let mut rows = Vec::new();
 		for i in 0..height {
 			let mut pixel_line = Vec::new();
 			for k in 0..width {
-				let index = ((i + loc.y) * line_length + (k + loc.x) * bytespp) as usize;
+				let index = layout.index(k + loc.x, i + loc.y);
 				pixel_line.push(Pixel{index});
 			}
 			rows.push(pixel_line);
@@ -100,17 +349,88 @@ impl Rectangle {
 			pixels: rows,
 		}
 	}
-	/// Fills a `Rectangle` with a given color
-	fn fill(&self, buffer: &mut [u8], rgb: (u8,u8,u8)) {
+	/// Gets the heap memory in bytes held by the `Rectangle`'s `Pixel`s
+	fn memory_usage(&self) -> usize {
+		let rows = self.pixels.capacity() * size_of::<Vec<Pixel>>();
+		rows + self.pixels.iter().map(|row| row.capacity() * size_of::<Pixel>()).sum::<usize>()
+	}
+	/// Gets the area of the screen covered by the `Rectangle`
+	pub fn rect(&self) -> Rect {
+		Rect::new(self.location.x, self.location.y, self.width, self.height)
+	}
+	/// Fills a `Rectangle` with a given color, blending it over the current contents by `alpha` if given
+	fn fill(&self, buffer: &mut [u8], rgb: (u8,u8,u8), alpha: Option<f32>) {
 		for row in self.pixels.iter() {
 			for p in row.iter() {
-				p.set_rgb(buffer, rgb.0, rgb.1, rgb.2);
+				match alpha {
+					Some(alpha) => p.blend_rgb(buffer, rgb.0, rgb.1, rgb.2, alpha),
+					None => p.set_rgb(buffer, rgb.0, rgb.1, rgb.2),
+				}
 			}
 		}
 	
 	}
 }
 
+/// A direction on the screen
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub enum Direction {
+	Up,
+	Down,
+	Left,
+	Right,
+}
+
+/// A borrowed view of a `Rectangle`'s `Pixel`s addressed by `(x, y)`
+#[derive(Clone,Copy)]
+pub struct PixelView<'a> {
+	rect: &'a Rectangle,
+}
+
+impl<'a> PixelView<'a> {
+	/// Gets the `Pixel` at `(x, y)`, or `None` if it is outside of the view
+	pub fn get(&self, x: usize, y: usize) -> Option<&'a Pixel> {
+		self.rect.pixels.get(y)?.get(x)
+	}
+	/// Gets the width of the view
+	pub fn width(&self) -> usize {
+		self.rect.width
+	}
+	/// Gets the height of the view
+	pub fn height(&self) -> usize {
+		self.rect.height
+	}
+}
+
+/// An edge of the screen
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub enum Edge {
+	Top,
+	Bottom,
+	Left,
+	Right,
+}
+
+/// How content is fitted into a `Window` by `Window::fit_rect`
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub enum FitMode {
+	/// Scales the content to fit inside, keeping its aspect ratio
+	Contain,
+	/// Scales the content to cover the whole `Window`, keeping its aspect ratio and cropping the rest
+	Cover,
+	/// Scales the content to the size of the `Window`, ignoring its aspect ratio
+	Stretch,
+}
+
+/// The direction of the stripes drawn by `Window::fill_stripes`
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub enum StripeAngle {
+	/// Stripes rising to the right, like `/`
+	Deg45,
+	/// Stripes falling to the right, like `\`
+	NegDeg45,
+}
+
 /// Represents a border around a `Window`
 pub struct Border {
 	pub top: Rectangle,
@@ -125,25 +445,643 @@ pub struct Window {
 	pub width: usize,
 	pub height: usize,
 	pub main_context: Rectangle,
+	/// Stacking order of the `Window`. Higher values are in front, equal values keep the order they were added in
+	pub z_index: i32,
 }
 
 impl Window {
-	/// Fills a `Window`'s `main_context` with the given color
-	fn fill(&self, buffer: &mut [u8], rgb: (u8,u8,u8)) {
-		self.main_context.fill(buffer,rgb);
+	/// Fills a `Window`'s `main_context` with the given color, blended by `alpha` if given
+	fn fill(&self, buffer: &mut [u8], rgb: (u8,u8,u8), alpha: Option<f32>) {
+		self.main_context.fill(buffer,rgb,alpha);
+	}
+	/// Fills a `Window`'s `border` with the given color, blended by `alpha` if given
+	fn fill_border(&self, buffer: &mut [u8], rgb: (u8,u8,u8), alpha: Option<f32>) {
+		if let Some(br) = &self.border {
+			br.top.fill(buffer, rgb, alpha);
+			br.left.fill(buffer, rgb, alpha);
+			br.right.fill(buffer, rgb, alpha);
+			br.bot.fill(buffer, rgb, alpha);
+		}
+	}
+	/// Fills a `Window`'s `main_context` with a conic gradient around the window-relative `center`
+	///
+	/// `colors` are spread evenly around the circle clockwise from `start_deg`, and the last color blends back into the first
+	pub fn fill_gradient_conic(&self, buffer: &mut [u8], center: Point, start_deg: f32, colors: &[(u8,u8,u8)]) {
+		if colors.is_empty() {
+			return;
+		}
+		let stops = colors.len();
+		let cx = center.x as f32 + 0.5;
+		let cy = center.y as f32 + 0.5;
+		for (y, row) in self.main_context.pixels.iter().enumerate() {
+			for (x, p) in row.iter().enumerate() {
+				let angle = (y as f32 + 0.5 - cy).atan2(x as f32 + 0.5 - cx).to_degrees() - start_deg;
+				let t = angle.rem_euclid(360.0) / 360.0 * stops as f32;
+				let i = (t.floor() as usize).min(stops - 1);
+				let rgb = blend(colors[i], colors[(i + 1) % stops], t - i as f32);
+				p.set_rgb(buffer, rgb.0, rgb.1, rgb.2);
+			}
+		}
+	}
+	/// Fills a `Window`'s `main_context` row-major from palette `indices`, one per `Pixel`
+	///
+	/// Returns `FBmanagerError::SizeMismatch` if there isn't exactly one index per `Pixel`
+	pub fn fill_indexed(&self, buffer: &mut [u8], indices: &[u8], palette: &[(u8,u8,u8); 256]) -> Result<(), FBmanagerError> {
+		if indices.len() != self.main_context.width * self.main_context.height {
+			return Err(FBmanagerError::SizeMismatch);
+		}
+		let pixels = self.main_context.pixels.iter().flat_map(|row| row.iter());
+		for (p, &i) in pixels.zip(indices) {
+			let rgb = palette[i as usize];
+			p.set_rgb(buffer, rgb.0, rgb.1, rgb.2);
+		}
+		Ok(())
+	}
+	/// Replaces each `Pixel` of a `Window`'s `main_context` with `below_rgb` if its luma is below `level`, otherwise with `above_rgb`
+	pub fn threshold(&self, buffer: &mut [u8], level: u8, below_rgb: (u8,u8,u8), above_rgb: (u8,u8,u8)) {
+		for row in self.main_context.pixels.iter() {
+			for p in row.iter() {
+				let rgb = if luma(p.get_rgb(buffer)) < level { below_rgb } else { above_rgb };
+				p.set_rgb(buffer, rgb.0, rgb.1, rgb.2);
+			}
+		}
 	}
-	/// Fills a `Window`'s `border` with the given color
-	fn fill_border(&self, buffer: &mut [u8], rgb: (u8,u8,u8)) {
+	/// Creates a new `Window` from the given template for a buffer with the given `Layout`
+	fn from_geometry(t: &WindowTemplate, layout: &Layout) -> Self {
+		//create border
+		let mut border = None;
+		let mut start_location = t.location;
+		let mut context_height = t.height;
+		let mut context_width = t.width;
+		if t.border_thickness > 0 {
+			//create top
+			let border_height = t.border_thickness;
+			let border_width = t.width;
+			let top = Rectangle::from_geometry(&t.location, border_height, border_width, layout);
+			//create bottom
+			let loc = t.location + (0, t.height - t.border_thickness);
+			let bot = Rectangle::from_geometry(&loc, border_height, border_width, layout);
+			//create right
+			let loc = t.location + (t.width - t.border_thickness, t.border_thickness);
+			let border_height = t.height - 2*t.border_thickness;
+			let border_width = t.border_thickness;
+			let right = Rectangle::from_geometry(&loc, border_height, border_width, layout);
+			//create left
+			let loc = t.location + (0, t.border_thickness);
+			let left = Rectangle::from_geometry(&loc, border_height, border_width, layout);
+			border = Some(Border {
+				top,
+				bot,
+				left,
+				right,
+			});
+
+			start_location += (t.border_thickness, t.border_thickness);
+			context_height -= 2*t.border_thickness;
+			context_width -= 2*t.border_thickness;
+		}
+		//create main_context
+		let main_context = Rectangle::from_geometry(&start_location, context_height, context_width, layout);
+		Window {
+			border,
+			width: t.width,
+			height: t.height,
+			main_context,
+			z_index: 0,
+		}
+	}
+	/// Gets the area of the screen covered by the `Window`, including its `border`
+	pub fn rect(&self) -> Rect {
+		let loc = self.location();
+		Rect::new(loc.x, loc.y, self.width, self.height)
+	}
+	/// Gets the location of the `Window`'s top left corner, including its `border`
+	pub fn location(&self) -> Point {
 		match &self.border {
-			Some(br) => {
-				br.top.fill(buffer, rgb);
-				br.left.fill(buffer, rgb);
-				br.right.fill(buffer, rgb);
-				br.bot.fill(buffer, rgb);
+			Some(br) => br.top.location,
+			None => self.main_context.location,
+		}
+	}
+	/// Gets the `Pixel` of the `main_context` at window-relative `(x, y)`, or `None` if it is outside of it
+	pub fn pixel_at(&self, x: usize, y: usize) -> Option<&Pixel> {
+		self.main_context.pixels.get(y)?.get(x)
+	}
+	/// Gets a view of the `main_context`'s `Pixel`s addressed by window-relative `(x, y)`
+	pub fn pixels_2d(&self) -> PixelView<'_> {
+		PixelView {rect: &self.main_context}
+	}
+	/// Gets the heap memory in bytes held by the `Window`'s `Rectangle`s
+	fn memory_usage(&self) -> usize {
+		let border = match &self.border {
+			Some(br) => br.top.memory_usage() + br.bot.memory_usage() + br.left.memory_usage() + br.right.memory_usage(),
+			None => 0,
+		};
+		border + self.main_context.memory_usage()
+	}
+	/// Replaces the `Pixel`s of a `Window`'s `main_context` whose channels are all within `tolerance` of `from_rgb` with `to_rgb`
+	pub fn replace_color(&self, buffer: &mut [u8], from_rgb: (u8,u8,u8), to_rgb: (u8,u8,u8), tolerance: u8) {
+		let close = |a: u8, b: u8| a.abs_diff(b) <= tolerance;
+		for row in self.main_context.pixels.iter() {
+			for p in row.iter() {
+				let (r, g, b) = p.get_rgb(buffer);
+				if close(r, from_rgb.0) && close(g, from_rgb.1) && close(b, from_rgb.2) {
+					p.set_rgb(buffer, to_rgb.0, to_rgb.1, to_rgb.2);
+				}
+			}
+		}
+	}
+	/// Fills the window-relative `rect` of a `Window`'s `main_context` with the given color, clipped to the `main_context`
+	pub fn fill_rect(&self, buffer: &mut [u8], rect: Rect, rgb: (u8,u8,u8)) {
+		let x_end = (rect.x + rect.width).min(self.main_context.width);
+		let y_end = (rect.y + rect.height).min(self.main_context.height);
+		for row in self.main_context.pixels.iter().take(y_end).skip(rect.y) {
+			for p in row.iter().take(x_end).skip(rect.x) {
+				p.set_rgb(buffer, rgb.0, rgb.1, rgb.2);
+			}
+		}
+	}
+	/// Fills a circle around the window-relative `center` with the given color, clipped to the `main_context`
+	pub fn fill_circle(&self, buffer: &mut [u8], center: Point, radius: usize, rgb: (u8,u8,u8)) {
+		self.blend_disc(buffer, center.x as f32 + 0.5, center.y as f32 + 0.5, radius as f32, rgb, 1.0);
+	}
+	/// Draws a frame of a spinner of eight dots around the window-relative `center` in the given color
+	///
+	/// The brightest dot moves one step clockwise for each `frame`, followed by a fading tail. The spinner is blended over the current contents,
+	/// so clear its area before drawing the next frame
+	pub fn draw_spinner(&self, buffer: &mut [u8], center: Point, radius: usize, frame: usize, rgb: (u8,u8,u8)) {
+		const DOTS: usize = 8;
+		let dot_radius = (radius as f32 / 5.0).max(1.0);
+		let orbit = radius as f32 - dot_radius;
+		let head = frame % DOTS;
+		for i in 0..DOTS {
+			// 0 for the head, increasing along the tail
+			let age = (head + DOTS - i) % DOTS;
+			let alpha = 1.0 - age as f32 / DOTS as f32;
+			let angle = (i as f32 / DOTS as f32 * 360.0 - 90.0).to_radians();
+			let cx = center.x as f32 + 0.5 + orbit * angle.cos();
+			let cy = center.y as f32 + 0.5 + orbit * angle.sin();
+			self.blend_disc(buffer, cx, cy, dot_radius, rgb, alpha);
+		}
+	}
+	/// Blends a disc with its center at window-relative `(cx, cy)` over the `main_context`
+	fn blend_disc(&self, buffer: &mut [u8], cx: f32, cy: f32, radius: f32, rgb: (u8,u8,u8), alpha: f32) {
+		let y_start = (cy - radius).floor().max(0.0) as usize;
+		let y_end = ((cy + radius).ceil().max(0.0) as usize).min(self.main_context.height);
+		let x_start = (cx - radius).floor().max(0.0) as usize;
+		let x_end = (cx + radius).ceil().max(0.0) as usize;
+		for (y, row) in self.main_context.pixels.iter().enumerate().take(y_end).skip(y_start) {
+			let dy = y as f32 + 0.5 - cy;
+			for (x, p) in row.iter().enumerate().take(x_end).skip(x_start) {
+				let dx = x as f32 + 0.5 - cx;
+				if dx * dx + dy * dy <= radius * radius {
+					p.blend_rgb(buffer, rgb.0, rgb.1, rgb.2, alpha);
+				}
+			}
+		}
+	}
+	/// Gets the window-relative origin that centers a `content_w` by `content_h` region in the `main_context`
+	///
+	/// Content larger than the `main_context` is placed at `(0, 0)` along that axis
+	pub fn center_rect(&self, content_w: usize, content_h: usize) -> Point {
+		Point::new(
+			self.main_context.width.saturating_sub(content_w) / 2,
+			self.main_context.height.saturating_sub(content_h) / 2,
+		)
+	}
+	/// Fills the window-relative `rect` with `fill_rgb` inside of a `border_px` wide outline of `border_rgb`, clipped to the `main_context`
+	pub fn fill_rect_bordered(&self, buffer: &mut [u8], rect: Rect, fill_rgb: (u8,u8,u8), border_rgb: (u8,u8,u8), border_px: usize) {
+		self.fill_rect(buffer, rect, border_rgb);
+		let inner = Rect::new(
+			rect.x + border_px,
+			rect.y + border_px,
+			rect.width.saturating_sub(2 * border_px),
+			rect.height.saturating_sub(2 * border_px),
+		);
+		self.fill_rect(buffer, inner, fill_rgb);
+	}
+	/// Draws a 1 pixel wide line between the window-relative points `from` and `to`, clipped to the `main_context`
+	pub fn draw_line(&self, buffer: &mut [u8], from: Point, to: Point, rgb: (u8,u8,u8)) {
+		let (mut x, mut y) = (from.x as isize, from.y as isize);
+		let (x1, y1) = (to.x as isize, to.y as isize);
+		let dx = (x1 - x).abs();
+		let dy = -(y1 - y).abs();
+		let sx = if x < x1 { 1 } else { -1 };
+		let sy = if y < y1 { 1 } else { -1 };
+		let mut err = dx + dy;
+		loop {
+			if let Some(p) = self.main_context.pixels.get(y as usize).and_then(|row| row.get(x as usize)) {
+				p.set_rgb(buffer, rgb.0, rgb.1, rgb.2);
+			}
+			if x == x1 && y == y1 {
+				break;
+			}
+			let e2 = 2 * err;
+			if e2 >= dy {
+				err += dy;
+				x += sx;
+			}
+			if e2 <= dx {
+				err += dx;
+				y += sy;
+			}
+		}
+	}
+	/// Draws `data` as a line chart filling the `main_context`, scaled between its minimum and maximum
+	///
+	/// `data` is linearly resampled to one sample per column
+	pub fn draw_sparkline(&self, buffer: &mut [u8], data: &[f32], rgb: (u8,u8,u8)) {
+		let width = self.main_context.width;
+		let height = self.main_context.height;
+		if data.is_empty() || width == 0 || height == 0 {
+			return;
+		}
+		let min = data.iter().cloned().fold(f32::INFINITY, f32::min);
+		let max = data.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+		let sample = |x: usize| {
+			if data.len() == 1 || width == 1 {
+				return data[0];
+			}
+			let pos = x as f32 * (data.len() - 1) as f32 / (width - 1) as f32;
+			let i = (pos.floor() as usize).min(data.len() - 2);
+			data[i] + (data[i + 1] - data[i]) * (pos - i as f32)
+		};
+		let to_y = |v: f32| {
+			let t = if max > min { (v - min) / (max - min) } else { 0.5 };
+			((1.0 - t) * (height - 1) as f32).round() as usize
+		};
+		let mut last = Point::new(0, to_y(sample(0)));
+		for x in 0..width {
+			let point = Point::new(x, to_y(sample(x)));
+			self.draw_line(buffer, last, point, rgb);
+			last = point;
+		}
+	}
+	/// Fills a `Window`'s `main_context` with value noise, blending from `low_rgb` to `high_rgb`
+	///
+	/// `scale` is the size of a noise cell in pixels. The same `seed` always gives the same noise
+	pub fn fill_noise(&self, buffer: &mut [u8], seed: u64, scale: f32, low_rgb: (u8,u8,u8), high_rgb: (u8,u8,u8)) {
+		let scale = if scale > 0.0 { scale } else { 1.0 };
+		for (y, row) in self.main_context.pixels.iter().enumerate() {
+			for (x, p) in row.iter().enumerate() {
+				let n = value_noise(seed, x as f32 / scale, y as f32 / scale);
+				let rgb = blend(low_rgb, high_rgb, n);
+				p.set_rgb(buffer, rgb.0, rgb.1, rgb.2);
+			}
+		}
+	}
+	/// Draws one bottom-aligned bar per bucket across the `main_context`, scaled so the largest count fills its height
+	///
+	/// Bars wider than a pixel are separated by a 1 pixel gap
+	pub fn draw_histogram(&self, buffer: &mut [u8], buckets: &[u32], rgb: (u8,u8,u8)) {
+		let width = self.main_context.width;
+		let height = self.main_context.height;
+		let max = buckets.iter().cloned().max().unwrap_or(0);
+		if max == 0 {
+			return;
+		}
+		let n = buckets.len();
+		for (i, &count) in buckets.iter().enumerate() {
+			let x = i * width / n;
+			let slot = (i + 1) * width / n - x;
+			let bar_width = if slot > 1 { slot - 1 } else { slot };
+			let bar_height = (count as u64 * height as u64 / max as u64) as usize;
+			self.fill_rect(buffer, Rect::new(x, height - bar_height, bar_width, bar_height), rgb);
+		}
+	}
+	/// Fills the columns of a `Window`'s `main_context` where `(x + phase) % step == 0` with the given color
+	pub fn fill_columns(&self, buffer: &mut [u8], step: usize, phase: usize, rgb: (u8,u8,u8)) {
+		if step == 0 {
+			return;
+		}
+		let first = (step - phase % step) % step;
+		for row in self.main_context.pixels.iter() {
+			for p in row.iter().skip(first).step_by(step) {
+				p.set_rgb(buffer, rgb.0, rgb.1, rgb.2);
+			}
+		}
+	}
+	/// Fills an arrow pointing in the given `direction` inside of the window-relative `rect`, clipped to the `main_context`
+	pub fn fill_arrow(&self, buffer: &mut [u8], rect: Rect, direction: Direction, rgb: (u8,u8,u8)) {
+		// a right-pointing arrow in unit coordinates: a shaft and a head
+		const ARROW: [(f32, f32); 7] = [(0.0, 0.3), (0.5, 0.3), (0.5, 0.0), (1.0, 0.5), (0.5, 1.0), (0.5, 0.7), (0.0, 0.7)];
+		let points: Vec<(f32, f32)> = ARROW.iter().map(|&(u, v)| {
+			let (u, v) = match direction {
+				Direction::Right => (u, v),
+				Direction::Left => (1.0 - u, v),
+				Direction::Down => (v, u),
+				Direction::Up => (v, 1.0 - u),
+			};
+			(rect.x as f32 + u * rect.width as f32, rect.y as f32 + v * rect.height as f32)
+		}).collect();
+		self.fill_polygon(buffer, &points, rgb);
+	}
+	/// Fills the polygon with the given window-relative corners by the even-odd rule, clipped to the `main_context`
+	fn fill_polygon(&self, buffer: &mut [u8], points: &[(f32, f32)], rgb: (u8,u8,u8)) {
+		if points.len() < 3 {
+			return;
+		}
+		let y_min = points.iter().map(|p| p.1).fold(f32::INFINITY, f32::min).max(0.0) as usize;
+		let y_max = points.iter().map(|p| p.1).fold(f32::NEG_INFINITY, f32::max).ceil().max(0.0) as usize;
+		let mut crossings = Vec::new();
+		for (y, row) in self.main_context.pixels.iter().enumerate().take(y_max).skip(y_min) {
+			let yc = y as f32 + 0.5;
+			crossings.clear();
+			for (i, &(x0, y0)) in points.iter().enumerate() {
+				let (x1, y1) = points[(i + 1) % points.len()];
+				if (y0 <= yc) != (y1 <= yc) {
+					crossings.push(x0 + (yc - y0) / (y1 - y0) * (x1 - x0));
+				}
+			}
+			crossings.sort_by(|a, b| a.partial_cmp(b).unwrap());
+			for span in crossings.chunks_exact(2) {
+				let start = (span[0] - 0.5).ceil().max(0.0) as usize;
+				let end = (span[1] - 0.5).ceil().max(0.0) as usize;
+				for p in row.iter().take(end).skip(start) {
+					p.set_rgb(buffer, rgb.0, rgb.1, rgb.2);
+				}
+			}
+		}
+	}
+	/// Draws a frame of an indeterminate progress bar: a `fg_rgb` block sliding across the `main_context` over `bg_rgb`
+	///
+	/// The block moves right with each `frame` and wraps around the right edge back to the left
+	pub fn draw_progress_indeterminate(&self, buffer: &mut [u8], frame: usize, fg_rgb: (u8,u8,u8), bg_rgb: (u8,u8,u8)) {
+		let width = self.main_context.width;
+		let height = self.main_context.height;
+		self.fill(buffer, bg_rgb, None);
+		if width == 0 {
+			return;
+		}
+		let block = (width / 4).max(1);
+		let step = (width / 50).max(1);
+		let pos = frame.wrapping_mul(step) % width;
+		self.fill_rect(buffer, Rect::new(pos, 0, block, height), fg_rgb);
+		if pos + block > width {
+			self.fill_rect(buffer, Rect::new(0, 0, pos + block - width, height), fg_rgb);
+		}
+	}
+	/// Gets the width divided by the height of a `Window`'s `main_context`, or 0.0 if it has no height
+	pub fn aspect_ratio(&self) -> f32 {
+		if self.main_context.height == 0 {
+			return 0.0;
+		}
+		self.main_context.width as f32 / self.main_context.height as f32
+	}
+	/// Gets the centered window-relative origin, width and height to scale `content_w` by `content_h` content into with the given `FitMode`
+	///
+	/// For `FitMode::Cover` this is the whole `main_context`; draw the part of the content given by `cover_crop` into it.
+	/// Content without area gets an empty rect at the center
+	pub fn fit_rect(&self, content_w: usize, content_h: usize, mode: FitMode) -> (Point, usize, usize) {
+		let (width, height) = (self.main_context.width, self.main_context.height);
+		if content_w == 0 || content_h == 0 {
+			return (Point::new(width / 2, height / 2), 0, 0);
+		}
+		let (w, h) = match mode {
+			FitMode::Stretch | FitMode::Cover => (width, height),
+			FitMode::Contain => {
+				let scale = (width as f64 / content_w as f64).min(height as f64 / content_h as f64);
+				let w = ((content_w as f64 * scale).round() as usize).max(1).min(width);
+				let h = ((content_h as f64 * scale).round() as usize).max(1).min(height);
+				(w, h)
+			}
+		};
+		(self.center_rect(w, h), w, h)
+	}
+	/// Gets the centered part of `content_w` by `content_h` content with the aspect ratio of the `main_context`, for `FitMode::Cover`
+	pub fn cover_crop(&self, content_w: usize, content_h: usize) -> Rect {
+		let (width, height) = (self.main_context.width, self.main_context.height);
+		if width == 0 || height == 0 || content_w == 0 || content_h == 0 {
+			return Rect::new(content_w / 2, content_h / 2, 0, 0);
+		}
+		let scale = (content_w as f64 / width as f64).min(content_h as f64 / height as f64);
+		let w = ((width as f64 * scale).round() as usize).clamp(1, content_w);
+		let h = ((height as f64 * scale).round() as usize).clamp(1, content_h);
+		Rect::new((content_w - w) / 2, (content_h - h) / 2, w, h)
+	}
+	/// Fills a `Window`'s `main_context` with the color `f` returns for each `Pixel`'s normalized `(u, v)` coordinates
+	///
+	/// `u` goes from 0.0 at the left column to 1.0 at the right column, `v` from 0.0 at the top row to 1.0 at the bottom row
+	pub fn fill_uv(&self, buffer: &mut [u8], f: impl Fn(f32, f32) -> (u8,u8,u8)) {
+		let normalize = |i: usize, len: usize| if len > 1 { i as f32 / (len - 1) as f32 } else { 0.0 };
+		for (y, row) in self.main_context.pixels.iter().enumerate() {
+			let v = normalize(y, self.main_context.height);
+			for (x, p) in row.iter().enumerate() {
+				let rgb = f(normalize(x, self.main_context.width), v);
+				p.set_rgb(buffer, rgb.0, rgb.1, rgb.2);
+			}
+		}
+	}
+	/// Fills a `Window`'s `main_context` with alternating diagonal bands of `rgb_a` and `rgb_b`, `width` pixels wide along each row
+	pub fn fill_stripes(&self, buffer: &mut [u8], width: usize, angle: StripeAngle, rgb_a: (u8,u8,u8), rgb_b: (u8,u8,u8)) {
+		let width = width.max(1) as isize;
+		for (y, row) in self.main_context.pixels.iter().enumerate() {
+			for (x, p) in row.iter().enumerate() {
+				let d = match angle {
+					StripeAngle::Deg45 => x as isize + y as isize,
+					StripeAngle::NegDeg45 => x as isize - y as isize,
+				};
+				let rgb = if d.rem_euclid(2 * width) < width { rgb_a } else { rgb_b };
+				p.set_rgb(buffer, rgb.0, rgb.1, rgb.2);
+			}
+		}
+	}
+	/// Fills the window-relative `rect` with corners rounded by `radius`, clipped to the `main_context`
+	pub fn fill_rounded_rect(&self, buffer: &mut [u8], rect: Rect, radius: usize, rgb: (u8,u8,u8)) {
+		let r = radius.min(rect.width / 2).min(rect.height / 2) as f32;
+		// the corner circles are centered on the edges of this inner area
+		let (x0, x1) = (rect.x as f32 + r, (rect.x + rect.width) as f32 - r);
+		let (y0, y1) = (rect.y as f32 + r, (rect.y + rect.height) as f32 - r);
+		let x_end = rect.x + rect.width;
+		for (y, row) in self.main_context.pixels.iter().enumerate().take(rect.y + rect.height).skip(rect.y) {
+			let cy = y as f32 + 0.5;
+			let dy = cy - cy.clamp(y0, y1);
+			for (x, p) in row.iter().enumerate().take(x_end).skip(rect.x) {
+				let cx = x as f32 + 0.5;
+				let dx = cx - cx.clamp(x0, x1);
+				if dx * dx + dy * dy <= r * r {
+					p.set_rgb(buffer, rgb.0, rgb.1, rgb.2);
+				}
+			}
+		}
+	}
+	/// Fills the corners of the window-relative `rect` outside of a rounding `radius` with `bg_rgb`, so content already drawn in `rect` looks rounded
+	///
+	/// Uses the same corner shape as `fill_rounded_rect`, clipped to the `main_context`
+	pub fn round_corners(&self, buffer: &mut [u8], rect: Rect, radius: usize, bg_rgb: (u8,u8,u8)) {
+		let radius = radius.min(rect.width / 2).min(rect.height / 2);
+		let r = radius as f32;
+		let (x0, x1) = (rect.x as f32 + r, (rect.x + rect.width) as f32 - r);
+		let (y0, y1) = (rect.y as f32 + r, (rect.y + rect.height) as f32 - r);
+		let x_end = rect.x + rect.width;
+		for (y, row) in self.main_context.pixels.iter().enumerate().take(rect.y + rect.height).skip(rect.y) {
+			let cy = y as f32 + 0.5;
+			let dy = cy - cy.clamp(y0, y1);
+			if dy == 0.0 {
+				continue;
+			}
+			for (x, p) in row.iter().enumerate().take(x_end).skip(rect.x) {
+				// only the corner squares can be outside of the rounding
+				if x >= rect.x + radius && x + radius < x_end {
+					continue;
+				}
+				let cx = x as f32 + 0.5;
+				let dx = cx - cx.clamp(x0, x1);
+				if dx * dx + dy * dy > r * r {
+					p.set_rgb(buffer, bg_rgb.0, bg_rgb.1, bg_rgb.2);
+				}
+			}
+		}
+	}
+	/// Draws a toggle switch in the window-relative `rect`: a rounded track in `on_rgb` or `off_rgb` with a round knob at the right or left end
+	pub fn draw_toggle(&self, buffer: &mut [u8], rect: Rect, on: bool, on_rgb: (u8,u8,u8), off_rgb: (u8,u8,u8), knob_rgb: (u8,u8,u8)) {
+		if rect.width == 0 || rect.height == 0 {
+			return;
+		}
+		let track = if on { on_rgb } else { off_rgb };
+		self.fill_rounded_rect(buffer, rect, rect.height / 2, track);
+		let half = rect.height as f32 / 2.0;
+		let knob_radius = (half - (rect.height as f32 / 10.0).max(1.0)).max(1.0);
+		let cx = if on { (rect.x + rect.width) as f32 - half } else { rect.x as f32 + half };
+		self.blend_disc(buffer, cx, rect.y as f32 + half, knob_radius, knob_rgb, 1.0);
+	}
+	/// Plots single pixels every `spacing` pixels in both directions, starting at the top left corner of the `main_context`
+	///
+	/// When `spacing` doesn't divide the `main_context` evenly the last row and column of dots are left short of the edges. A `spacing` of 0 draws nothing
+	pub fn draw_dot_grid(&self, buffer: &mut [u8], spacing: usize, rgb: (u8,u8,u8)) {
+		if spacing == 0 {
+			return;
+		}
+		for row in self.main_context.pixels.iter().step_by(spacing) {
+			for p in row.iter().step_by(spacing) {
+				p.set_rgb(buffer, rgb.0, rgb.1, rgb.2);
+			}
+		}
+	}
+}
+
+/// A handle to a `Window` in a `WindowStore`
+///
+/// Handles stay valid while other `Window`s are added or removed. The handle of a removed `Window` never refers to another `Window`
+#[derive(Clone,Copy,Debug,PartialEq,Eq,Hash)]
+pub struct WindowId {
+	index: usize,
+	generation: usize,
+}
+
+impl From<usize> for WindowId {
+	/// Gets the handle of the `Window` created from the `WindowTemplate` at the given index in `FBmanager::new`
+	///
+	/// #Examples
+	///
+	/// ```
+	/// use framebuffer_manager::WindowId;
+	///
+	/// let id = WindowId::from(2);
+	/// assert_eq!(id, 2.into());
+	/// ```
+	fn from(index: usize) -> Self {
+		WindowId {index, generation: 0}
+	}
+}
+
+struct Slot {
+	generation: usize,
+	// when the current `Window` was added, for keeping the order of equal `z_index`es
+	seq: u64,
+	window: Option<Window>,
+}
+
+/// Holds the `Window`s of an `FBmanager`, addressed by `WindowId`
+///
+/// The slots of removed `Window`s are reused, with a new generation so old handles don't match
+#[derive(Default)]
+pub struct WindowStore {
+	slots: Vec<Slot>,
+	free: Vec<usize>,
+	next_seq: u64,
+}
+
+impl WindowStore {
+	/// Gets the `Window` with the given `id`, if it exists
+	pub fn get(&self, id: WindowId) -> Option<&Window> {
+		match self.slots.get(id.index) {
+			Some(slot) if slot.generation == id.generation => slot.window.as_ref(),
+			_ => None,
+		}
+	}
+	/// Gets the `Window` with the given `id` mutably, if it exists
+	pub fn get_mut(&mut self, id: WindowId) -> Option<&mut Window> {
+		match self.slots.get_mut(id.index) {
+			Some(slot) if slot.generation == id.generation => slot.window.as_mut(),
+			_ => None,
+		}
+	}
+	/// Checks if a `Window` with the given `id` exists
+	pub fn contains(&self, id: WindowId) -> bool {
+		self.get(id).is_some()
+	}
+	/// Gets the number of `Window`s
+	pub fn len(&self) -> usize {
+		self.slots.len() - self.free.len()
+	}
+	/// Checks if there are no `Window`s
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+	/// Iterates over the `Window`s with their ids
+	pub fn iter(&self) -> impl Iterator<Item = (WindowId, &Window)> {
+		self.slots.iter().enumerate().filter_map(|(index, slot)| {
+			slot.window.as_ref().map(|w| (WindowId {index, generation: slot.generation}, w))
+		})
+	}
+	/// Gets the `Window`s with their ids from back to front by `z_index`, equal values in the order they were added in
+	fn by_z(&self) -> Vec<(WindowId, &Window)> {
+		let mut windows: Vec<_> = self.slots.iter().enumerate().filter_map(|(index, slot)| {
+			slot.window.as_ref().map(|w| (slot.seq, WindowId {index, generation: slot.generation}, w))
+		}).collect();
+		windows.sort_by_key(|(seq, _, w)| (w.z_index, *seq));
+		windows.into_iter().map(|(_, id, w)| (id, w)).collect()
+	}
+	/// Adds a `Window`, reusing a free slot if there is one
+	fn insert(&mut self, window: Window) -> WindowId {
+		let seq = self.next_seq;
+		self.next_seq += 1;
+		match self.free.pop() {
+			Some(index) => {
+				let slot = &mut self.slots[index];
+				slot.seq = seq;
+				slot.window = Some(window);
+				WindowId {index, generation: slot.generation}
 			},
-			_ => {}
+			None => {
+				self.slots.push(Slot {generation: 0, seq, window: Some(window)});
+				WindowId {index: self.slots.len() - 1, generation: 0}
+			}
 		}
 	}
+	/// Removes the `Window` with the given `id` and frees its slot
+	fn remove(&mut self, id: WindowId) -> Option<Window> {
+		self.get(id)?;
+		let slot = &mut self.slots[id.index];
+		slot.generation += 1;
+		self.free.push(id.index);
+		slot.window.take()
+	}
+}
+
+impl Index<WindowId> for WindowStore {
+	type Output = Window;
+
+	/// Gets the `Window` with the given `id`. Panics if it doesn't exist
+	fn index(&self, id: WindowId) -> &Window {
+		self.get(id).expect("no window exists with the given id")
+	}
+}
+
+impl IndexMut<WindowId> for WindowStore {
+	fn index_mut(&mut self, id: WindowId) -> &mut Window {
+		self.get_mut(id).expect("no window exists with the given id")
+	}
 }
 
 /// A template to create a `Window`
@@ -155,69 +1093,279 @@ pub struct WindowTemplate {
 	pub border_thickness: usize,
 }
 
+const KDGETMODE: libc::c_ulong = 0x4B3B;
+const KD_TEXT: libc::c_int = 0x00;
+const KD_GRAPHICS: libc::c_int = 0x01;
+
+const FB_TYPE_PACKED_PIXELS: u32 = 0;
+
+const RAW_MAGIC: &[u8; 4] = b"FBMR";
+const RAW_HEADER_LEN: usize = 28;
+
 /// A container to manage the framebuffer. Abstracts away from the buffer that represents the screen
 pub struct FBmanager {
 	pub framebuffer: Framebuffer,
 	pub buffer: Vec<u8>,
-	pub windows: Vec<Window>,
+	pub windows: WindowStore,
+	pixel_format: PixelFormat,
+	// the buffer reordered into `pixel_format`, reused between draws
+	frame: Vec<u8>,
+	// the frame composed by `slide_in`, reused between animation frames
+	scratch: Vec<u8>,
+	last_draw: Option<Instant>,
+	buffers: Vec<Vec<u8>>,
+	layout: Layout,
+	// the area of the screen covered by the buffer
+	bounds: Rect,
+	frozen: HashSet<WindowId>,
+	dirty: Vec<Rect>,
+	fill_colors: HashMap<WindowId, (u8,u8,u8)>,
+	global_alpha: Option<f32>,
+	#[cfg(feature = "remote")]
+	remote: Option<std::net::TcpStream>,
+	clear_on_exit: Option<ClearGuard>,
+}
+
+/// Blanks an area of the screen when dropped, through its own handle to the device
+struct ClearGuard {
+	device: File,
+	line_length: usize,
+	bytespp: usize,
+	bounds: Rect,
+}
+
+impl ClearGuard {
+	/// Drops the guard without blanking the screen
+	fn disarm(mut self) {
+		self.bounds.height = 0;
+	}
+}
+
+impl Drop for ClearGuard {
+	fn drop(&mut self) {
+		let black = vec![0u8; self.bounds.width * self.bytespp];
+		for y in self.bounds.y..self.bounds.y + self.bounds.height {
+			let offset = y * self.line_length + self.bounds.x * self.bytespp;
+			// nothing can be done about a failed write while dropping
+			let _ = self.device.write_at(&black, offset as u64);
+		}
+	}
+}
+
+/// A handle to an extra buffer of an `FBmanager`, created with `create_buffer`
+#[derive(Clone,Copy,Debug,PartialEq,Eq,Hash)]
+pub struct BufferId(usize);
+
+/// Computes the refresh rate of the timings in `info` like `fbset` does. Returns `None` without a pixel clock
+fn refresh_rate_hz(info: &VarScreeninfo) -> Option<f32> {
+	const FB_VMODE_INTERLACED: u32 = 1;
+	const FB_VMODE_DOUBLE: u32 = 2;
+	if info.pixclock == 0 {
+		return None;
+	}
+	let htotal = (info.xres + info.left_margin + info.right_margin + info.hsync_len) as f32;
+	let mut vtotal = (info.yres + info.upper_margin + info.lower_margin + info.vsync_len) as f32;
+	if info.vmode & FB_VMODE_INTERLACED != 0 {
+		vtotal /= 2.0;
+	}
+	if info.vmode & FB_VMODE_DOUBLE != 0 {
+		vtotal *= 2.0;
+	}
+	if htotal == 0.0 || vtotal == 0.0 {
+		return None;
+	}
+	// pixclock is the length of a pixel in picoseconds
+	let pixels_per_second = 1e12 / info.pixclock as f32;
+	Some(pixels_per_second / (htotal * vtotal))
+}
+
+/// Builds the header `dump_raw` writes for a buffer covering `bounds` with the given depth and stride
+fn raw_header(bounds: Rect, bits_per_pixel: usize, stride: usize) -> [u8; RAW_HEADER_LEN] {
+	let b = &bounds;
+	let fields = [b.x, b.y, b.width, b.height, bits_per_pixel, stride];
+	let mut header = [0u8; RAW_HEADER_LEN];
+	header[..4].copy_from_slice(RAW_MAGIC);
+	for (i, field) in fields.iter().enumerate() {
+		header[4 + i*4..8 + i*4].copy_from_slice(&(*field as u32).to_le_bytes());
+	}
+	header
+}
+
+/// Reads a dump from `reader`, checking it starts with `header` and holds exactly `len` bytes of buffer after it
+fn read_raw<R: Read>(reader: &mut R, header: &[u8; RAW_HEADER_LEN], len: usize) -> io::Result<Vec<u8>> {
+	let mut found = [0u8; RAW_HEADER_LEN];
+	reader.read_exact(&mut found)?;
+	if &found != header {
+		return Err(io::Error::new(io::ErrorKind::InvalidData, "dump doesn't match the screen geometry"));
+	}
+	// one byte past `len` is enough to tell a too long dump without reading all of it
+	let mut buffer = Vec::with_capacity(len + 1);
+	reader.take(len as u64 + 1).read_to_end(&mut buffer)?;
+	if buffer.len() != len {
+		return Err(io::Error::new(io::ErrorKind::InvalidData, "dump has the wrong length"));
+	}
+	Ok(buffer)
+}
+
+/// Rotates the `width` by `height` pixels of `buffer` by 180°, leaving any padding at the end of its lines untouched
+fn rotate_180(buffer: &mut [u8], layout: &Layout, width: usize, height: usize) {
+	let Layout {stride, bytespp, ..} = *layout;
+	for k in 0..(width * height) / 2 {
+		let (x, y) = (k % width, k / width);
+		let a = y * stride + x * bytespp;
+		let b = (height - 1 - y) * stride + (width - 1 - x) * bytespp;
+		for i in 0..bytespp {
+			buffer.swap(a + i, b + i);
+		}
+	}
+}
+
+/// Hashes `bytes` with 64-bit FNV-1a
+fn fnv1a(bytes: &[u8]) -> u64 {
+	bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| (hash ^ b as u64).wrapping_mul(0x0000_0100_0000_01b3))
+}
+
+/// Adds `rect` to `regions`, merging it with the regions it overlaps until none overlap
+fn add_region(regions: &mut Vec<Rect>, mut rect: Rect) {
+	while let Some(i) = regions.iter().position(|r| r.overlaps(&rect)) {
+		rect = rect.union(&regions.swap_remove(i));
+	}
+	regions.push(rect);
+}
+
+/// Writes the `region` of `buffer`, laid out with the given `Layout`, to the `Framebuffer`
+///
+/// `region` is in screen coordinates and must be inside of the area covered by the buffer.
+/// The `Pixel`s are reordered into `format` through `frame` if needed
+fn write_frame(framebuffer: &mut Framebuffer, frame: &mut Vec<u8>, buffer: &[u8], format: PixelFormat, layout: &Layout, region: Rect) {
+	let line_length = framebuffer.fix_screen_info.line_length as usize;
+	let bytespp = layout.bytespp;
+	let src = if format == PixelFormat::BGR {
+		buffer
+	} else {
+		frame.resize(buffer.len(), 0);
+		for y in region.y..region.y + region.height {
+			for x in region.x..region.x + region.width {
+				let i = layout.index(x, y);
+				frame[i..i + bytespp].copy_from_slice(&buffer[i..i + bytespp]);
+				frame[i + format.red] = buffer[i + 2];
+				frame[i + format.green] = buffer[i + 1];
+				frame[i + format.blue] = buffer[i];
+			}
+		}
+		&frame[..]
+	};
+	let screen = unsafe { framebuffer.frame.as_mut_slice() };
+	let width = region.width * bytespp;
+	if layout.stride == line_length && width == line_length {
+		// whole lines are contiguous on the screen as well
+		let start = layout.index(region.x, region.y);
+		let offset = region.y * line_length;
+		let len = region.height * line_length;
+		screen[offset..offset + len].copy_from_slice(&src[start..start + len]);
+		return;
+	}
+	for y in region.y..region.y + region.height {
+		let start = layout.index(region.x, y);
+		let offset = y * line_length + region.x * bytespp;
+		screen[offset..offset + width].copy_from_slice(&src[start..start + width]);
+	}
 }
 
 impl FBmanager {
 	/// Creates a new `FBmanager` using the given template
-	pub fn new(template: &[WindowTemplate]) -> Self {
-		let framebuffer = Framebuffer::new("/dev/fb0").unwrap();
-		let height = framebuffer.var_screen_info.yres;
-		let line_length = framebuffer.fix_screen_info.line_length;
-		let buffer = vec![0u8; (line_length*height) as usize];
-		let mut window_holder = Vec::new();
+	///
+	/// The `Window` built from `template[i]` has the id `WindowId::from(i)`.
+	/// Fails if `/dev/fb0` can't be opened or isn't laid out in linear scanlines
+	pub fn new(template: &[WindowTemplate]) -> Result<Self, FBmanagerError> {
+		let framebuffer = Framebuffer::new("/dev/fb0")?;
+		FBmanager::from_framebuffer(framebuffer, template)
+	}
+	/// Creates a new `FBmanager` like `new`, with a buffer only covering the bounding box of the template's `Window`s
+	///
+	/// Saves memory and only the bounding box is written to the screen by `draw`. `Window`s added later must fit inside it
+	pub fn new_cropped(template: &[WindowTemplate]) -> Result<Self, FBmanagerError> {
+		let framebuffer = Framebuffer::new("/dev/fb0")?;
+		FBmanager::from_framebuffer_cropped(framebuffer, template)
+	}
+	/// Creates a new `FBmanager` on an already opened `Framebuffer` using the given template
+	///
+	/// Returns `FBmanagerError::UnsupportedLayout` if the `Framebuffer` isn't laid out in linear scanlines
+	pub fn from_framebuffer(framebuffer: Framebuffer, template: &[WindowTemplate]) -> Result<Self, FBmanagerError> {
+		let bounds = Rect::new(0, 0, framebuffer.var_screen_info.xres as usize, framebuffer.var_screen_info.yres as usize);
+		let line_length = framebuffer.fix_screen_info.line_length as usize;
+		let bytespp = (framebuffer.var_screen_info.bits_per_pixel / 8) as usize;
+		FBmanager::with_layout(framebuffer, template, bounds, Layout::new(line_length, bytespp))
+	}
+	/// Creates a new `FBmanager` like `new_cropped` on an already opened `Framebuffer`
+	pub fn from_framebuffer_cropped(framebuffer: Framebuffer, template: &[WindowTemplate]) -> Result<Self, FBmanagerError> {
+		let screen = Rect::new(0, 0, framebuffer.var_screen_info.xres as usize, framebuffer.var_screen_info.yres as usize);
+		let bounds = template.iter()
+			.map(|t| Rect::new(t.location.x, t.location.y, t.width, t.height))
+			.fold(None, |acc: Option<Rect>, r| Some(acc.map_or(r, |acc| acc.union(&r))))
+			.map_or(Rect::new(0, 0, 0, 0), |r| r.intersection(&screen));
+		let bytespp = (framebuffer.var_screen_info.bits_per_pixel / 8) as usize;
+		let layout = Layout {origin: Point::new(bounds.x, bounds.y), stride: bounds.width * bytespp, bytespp};
+		FBmanager::with_layout(framebuffer, template, bounds, layout)
+	}
+	/// Creates a new `FBmanager` with a buffer covering `bounds` of the screen with the given `Layout`
+	fn with_layout(framebuffer: Framebuffer, template: &[WindowTemplate], bounds: Rect, layout: Layout) -> Result<Self, FBmanagerError> {
+		if !FBmanager::is_linear_framebuffer(&framebuffer) {
+			return Err(FBmanagerError::UnsupportedLayout(framebuffer.fix_screen_info.fb_type));
+		}
+		let buffer = vec![0u8; layout.stride * bounds.height];
+		let mut window_holder = WindowStore::default();
 		for t in template.iter() {
-			//create border
-			let mut border = None;
-			let mut start_location = t.location;
-			let mut context_height = t.height;
-			let mut context_width = t.width;
-			if t.border_thickness > 0 {
-				//create top
-				let border_height = t.border_thickness;
-				let border_width = t.width;
-				let top = Rectangle::from_dimensions(&t.location,border_height, border_width, &framebuffer);
-				//create bottom
-				let loc = t.location + (0, t.height - t.border_thickness);
-				let bot = Rectangle::from_dimensions(&loc, border_height, border_width, &framebuffer);
-				//create right
-				let loc = t.location + (t.width - t.border_thickness, t.border_thickness); 
-				let border_height = t.height - 2*t.border_thickness;
-				let border_width = t.border_thickness;
-				let right = Rectangle::from_dimensions(&loc, border_height, border_width, &framebuffer);
-				//create left
-				let loc = t.location + (0, t.border_thickness);
-				let left = Rectangle::from_dimensions(&loc, border_height, border_width, &framebuffer);
-				border = Some(Border {
-					top,
-					bot,
-					left,
-					right,
-				});
-				
-				start_location += (t.border_thickness, t.border_thickness);
-				context_height -= 2*t.border_thickness;
-				context_width -= 2*t.border_thickness;
-			}
-			//create main_context
-			let main_context = Rectangle::from_dimensions(&start_location, context_height, context_width, &framebuffer); 
-			let window = Window {
-				border,
-				width: t.width,
-				height: t.height,
-				main_context,
-			};
-			window_holder.push(window);
+			window_holder.insert(Window::from_geometry(t, &layout));
 		}
-		FBmanager {
+		let pixel_format = PixelFormat::detect(&framebuffer.var_screen_info);
+		Ok(FBmanager {
 			framebuffer,
 			buffer,
 			windows: window_holder,
+			pixel_format,
+			frame: Vec::new(),
+			scratch: Vec::new(),
+			last_draw: None,
+			buffers: Vec::new(),
+			layout,
+			bounds,
+			frozen: HashSet::new(),
+			dirty: Vec::new(),
+			fill_colors: HashMap::new(),
+			global_alpha: None,
+			#[cfg(feature = "remote")]
+			remote: None,
+			clear_on_exit: None,
+		})
+	}
+	/// Consumes the `FBmanager` and gives back its `Framebuffer`. The buffer and `Window`s are dropped
+	///
+	/// The screen is never cleared here, regardless of `set_clear_on_exit`
+	pub fn into_framebuffer(self) -> Framebuffer {
+		let FBmanager {framebuffer, clear_on_exit, ..} = self;
+		if let Some(guard) = clear_on_exit {
+			guard.disarm();
 		}
+		framebuffer
+	}
+	/// Sets whether the area of the screen covered by the buffer is blanked when the `FBmanager` is dropped
+	///
+	/// Off by default, leaving the last drawn frame on the screen. Fails if the handle to the device can't be duplicated for blanking it
+	pub fn set_clear_on_exit(&mut self, clear: bool) -> io::Result<()> {
+		if !clear {
+			if let Some(guard) = self.clear_on_exit.take() {
+				guard.disarm();
+			}
+		} else if self.clear_on_exit.is_none() {
+			self.clear_on_exit = Some(ClearGuard {
+				device: self.framebuffer.device.try_clone()?,
+				line_length: self.framebuffer.fix_screen_info.line_length as usize,
+				bytespp: self.layout.bytespp,
+				bounds: self.bounds,
+			});
+		}
+		Ok(())
 	}
 	/// Enables Framebuffer graphics. *Must be enabled to draw to the screen*
 	/// **Must call** `disable_graphics()` **before the process exits**
@@ -228,17 +1376,410 @@ impl FBmanager {
 	pub fn disable_graphics() -> Result<i32, FramebufferError> {
 		Framebuffer::set_kd_mode(KdMode::Text)
 	}
+	/// Reads back the current mode of the terminal on standard input, e.g. to check that `enable_graphics` took effect
+	///
+	/// Fails if standard input isn't a virtual terminal, or with `io::ErrorKind::InvalidData` if the mode is neither text nor graphics
+	pub fn query_kd_mode() -> io::Result<KdMode> {
+		let mut mode: libc::c_int = 0;
+		if unsafe { libc::ioctl(0, KDGETMODE, &mut mode) } == -1 {
+			return Err(io::Error::last_os_error());
+		}
+		match mode {
+			KD_TEXT => Ok(KdMode::Text),
+			KD_GRAPHICS => Ok(KdMode::Graphics),
+			_ => Err(io::Error::new(io::ErrorKind::InvalidData, "unknown terminal mode")),
+		}
+	}
+	/// Builds a `Window` from the given template and adds it to the `FBmanager`. Returns the new `Window`'s id
+	///
+	/// Ids of existing `Window`s are not affected. Fails if the `Window` doesn't fit in the area covered by the buffer
+	pub fn add_window(&mut self, template: &WindowTemplate) -> Result<WindowId, FBmanagerError> {
+		let t = template;
+		let area = Rect::new(t.location.x, t.location.y, t.width, t.height);
+		if !self.bounds.contains(&area) || 2*t.border_thickness > t.width || 2*t.border_thickness > t.height {
+			return Err(FBmanagerError::OutOfBounds);
+		}
+		Ok(self.windows.insert(Window::from_geometry(t, &self.layout)))
+	}
+	/// Removes the `Window` with the given `id` and returns it
+	///
+	/// The ids of the remaining `Window`s stay valid
+	pub fn remove_window(&mut self, id: WindowId) -> Option<Window> {
+		self.frozen.remove(&id);
+		self.fill_colors.remove(&id);
+		self.windows.remove(id)
+	}
+	/// Freezes or unfreezes the `Window` with the given `id`. The `FBmanager`'s fill and draw methods skip frozen `Window`s
+	///
+	/// `Window` methods called directly on a buffer are not affected
+	pub fn set_window_frozen(&mut self, id: WindowId, frozen: bool) {
+		if frozen {
+			self.frozen.insert(id);
+		} else {
+			self.frozen.remove(&id);
+		}
+	}
+	/// Checks if the `Window` with the given `id` is frozen
+	pub fn is_window_frozen(&self, id: WindowId) -> bool {
+		self.frozen.contains(&id)
+	}
+	/// Gets the areas of the screen changed through the `FBmanager` since the last `draw` or `clear_dirty`
+	///
+	/// Overlapping areas are merged into their bounding `Rect`
+	pub fn dirty_regions(&self) -> &[Rect] {
+		&self.dirty
+	}
+	/// Gets the bounding `Rect` of all dirty regions, or `None` if nothing changed
+	pub fn dirty_bounds(&self) -> Option<Rect> {
+		let (first, rest) = self.dirty.split_first()?;
+		Some(rest.iter().fold(*first, |bounds, r| bounds.union(r)))
+	}
+	/// Forgets the areas recorded as changed
+	pub fn clear_dirty(&mut self) {
+		self.dirty.clear();
+	}
+	/// Records an area of the screen as changed, e.g. after drawing to the buffer with `Window` methods
+	///
+	/// `Window`s overlapping the area lose their `window_fill_color`
+	pub fn mark_dirty(&mut self, rect: Rect) {
+		let rect = rect.intersection(&self.bounds);
+		if rect.width == 0 || rect.height == 0 {
+			return;
+		}
+		let windows = &self.windows;
+		self.fill_colors.retain(|&id, _| windows.get(id).is_some_and(|w| !w.main_context.rect().overlaps(&rect)));
+		add_region(&mut self.dirty, rect);
+	}
+	/// Gets the color the `Window` with the given `id` was last filled with by `fill`
+	///
+	/// Returns `None` if anything else was drawn over it through the `FBmanager` since, or recorded with `mark_dirty`
+	pub fn window_fill_color(&self, id: WindowId) -> Option<(u8,u8,u8)> {
+		self.fill_colors.get(&id).cloned()
+	}
+	/// Iterates over the `Window`s from back to front by their `z_index`
+	pub fn windows_by_z(&self) -> impl Iterator<Item = (WindowId, &Window)> {
+		self.windows.by_z().into_iter()
+	}
+	/// Moves the `Window` with the given `id` in front of all other `Window`s
+	pub fn bring_to_front(&mut self, id: WindowId) {
+		let top = self.windows.iter().filter(|(other, _)| *other != id).map(|(_, w)| w.z_index).max();
+		if let Some(top) = top {
+			let window = &mut self.windows[id];
+			window.z_index = window.z_index.max(top.saturating_add(1));
+		}
+	}
+	/// Makes `fill`, `fill_border`, `fill_target` and `fill_border_target` blend their color over the current contents by `alpha` while set.
+	/// The operations of `execute` and `Marquee::render` are blended the same way
+	///
+	/// `alpha` is clamped to 0.0..=1.0. `None` goes back to overwriting
+	pub fn set_global_alpha(&mut self, alpha: Option<f32>) {
+		self.global_alpha = alpha.map(|a| a.clamp(0.0, 1.0));
+	}
+	/// Runs `draw` on the `Window` with the given `id` in the buffer, then blends what it changed in the `main_context` by the global alpha
+	pub(crate) fn draw_blended<F: FnOnce(&Window, &mut [u8])>(&mut self, id: WindowId, draw: F) {
+		let window = &self.windows[id];
+		let alpha = match self.global_alpha {
+			Some(alpha) => alpha,
+			None => return draw(window, &mut self.buffer),
+		};
+		let pixels = &window.main_context.pixels;
+		let before: Vec<(u8,u8,u8)> = pixels.iter().flatten().map(|p| p.get_rgb(&self.buffer)).collect();
+		draw(window, &mut self.buffer);
+		for (p, old) in pixels.iter().flatten().zip(before) {
+			let (r, g, b) = blend(old, p.get_rgb(&self.buffer), alpha);
+			p.set_rgb(&mut self.buffer, r, g, b);
+		}
+	}
 	/// Fills the `Window` with the given `id` to the given color
-	pub fn fill(&mut self, id: usize, rgb: (u8,u8,u8)) {
-		self.windows[id].fill(&mut self.buffer, rgb);
+	pub fn fill(&mut self, id: WindowId, rgb: (u8,u8,u8)) {
+		if self.is_window_frozen(id) {
+			return;
+		}
+		self.mark_dirty(self.windows[id].main_context.rect());
+		self.windows[id].fill(&mut self.buffer, rgb, self.global_alpha);
+		if self.global_alpha.is_none() {
+			self.fill_colors.insert(id, rgb);
+		}
 	}
 	/// Fills the `Window` with the given `id`'s border to the given color
-	pub fn fill_border(&mut self, id: usize, rgb: (u8,u8,u8)) {
-		self.windows[id].fill_border(&mut self.buffer, rgb);
+	pub fn fill_border(&mut self, id: WindowId, rgb: (u8,u8,u8)) {
+		if self.is_window_frozen(id) {
+			return;
+		}
+		let sides = match &self.windows[id].border {
+			Some(br) => vec![br.top.rect(), br.bot.rect(), br.left.rect(), br.right.rect()],
+			None => Vec::new(),
+		};
+		for side in sides {
+			self.mark_dirty(side);
+		}
+		self.windows[id].fill_border(&mut self.buffer, rgb, self.global_alpha);
+	}
+	/// Blends the contents of the `Window`s `a_id` and `b_id` by `t` into the `Window` `dst_id`, 0.0 giving all A and 1.0 all B
+	///
+	/// Only the overlap of the three `main_context` sizes is blended. Returns `FBmanagerError::InvalidWindow` if an id doesn't exist
+	pub fn crossfade(&mut self, a_id: WindowId, b_id: WindowId, dst_id: WindowId, t: f32) -> Result<(), FBmanagerError> {
+		let a = &self.windows.get(a_id).ok_or(FBmanagerError::InvalidWindow)?.main_context;
+		let b = &self.windows.get(b_id).ok_or(FBmanagerError::InvalidWindow)?.main_context;
+		let dst = &self.windows.get(dst_id).ok_or(FBmanagerError::InvalidWindow)?.main_context;
+		if self.frozen.contains(&dst_id) {
+			return Ok(());
+		}
+		let width = a.width.min(b.width).min(dst.width);
+		let height = a.height.min(b.height).min(dst.height);
+		for y in 0..height {
+			for x in 0..width {
+				let rgb = blend(a.pixels[y][x].get_rgb(&self.buffer), b.pixels[y][x].get_rgb(&self.buffer), t);
+				dst.pixels[y][x].set_rgb(&mut self.buffer, rgb.0, rgb.1, rgb.2);
+			}
+		}
+		let area = Rect::new(dst.location.x, dst.location.y, width, height);
+		self.mark_dirty(area);
+		Ok(())
+	}
+	/// Draws a blurred drop shadow behind the `Window` with the given `id`
+	///
+	/// The shadow is the `Window`'s outer bounds shifted by `offset`, softened over `blur` pixels and blended black at `alpha` (0.0 to 1.0) over the current contents.
+	/// Call this **before** filling the `Window` so the `Window` is drawn on top of its shadow.
+	pub fn draw_shadow(&mut self, id: WindowId, offset: (isize,isize), blur: usize, alpha: f32) {
+		if self.is_window_frozen(id) {
+			return;
+		}
+		let bounds = self.bounds;
+		let layout = self.layout;
+		let window = &self.windows[id];
+		let loc = window.location();
+		let x0 = loc.x as isize + offset.0;
+		let y0 = loc.y as isize + offset.1;
+		let x1 = x0 + window.width as isize;
+		let y1 = y0 + window.height as isize;
+		let blur = blur as isize;
+		// coverage of a box blur of width 2*blur across an edge
+		let coverage = |p: isize, start: isize, end: isize| -> f32 {
+			if blur == 0 {
+				return if p >= start && p < end { 1.0 } else { 0.0 };
+			}
+			let center = p as f32 + 0.5;
+			let inside = (center - (start - blur) as f32).min((end + blur) as f32 - center);
+			(inside / (2 * blur) as f32).clamp(0.0, 1.0)
+		};
+		let (bx0, by0) = (bounds.x as isize, bounds.y as isize);
+		let (bx1, by1) = (bx0 + bounds.width as isize, by0 + bounds.height as isize);
+		let (sx0, sx1) = ((x0 - blur).max(bx0), (x1 + blur).min(bx1));
+		let (sy0, sy1) = ((y0 - blur).max(by0), (y1 + blur).min(by1));
+		if sx0 >= sx1 || sy0 >= sy1 {
+			return;
+		}
+		self.mark_dirty(Rect::new(sx0 as usize, sy0 as usize, (sx1 - sx0) as usize, (sy1 - sy0) as usize));
+		for y in sy0..sy1 {
+			let cy = coverage(y, y0, y1);
+			for x in sx0..sx1 {
+				let a = alpha * cy * coverage(x, x0, x1);
+				if a > 0.0 {
+					let pixel = Pixel{index: layout.index(x as usize, y as usize)};
+					pixel.blend_rgb(&mut self.buffer, 0, 0, 0, a);
+				}
+			}
+		}
+	}
+	/// Gets the approximate memory in bytes held by the `FBmanager`, including its buffer and `Window` geometry
+	pub fn memory_usage(&self) -> usize {
+		let store = self.windows.slots.capacity() * size_of::<Slot>() + self.windows.free.capacity() * size_of::<usize>();
+		let windows: usize = self.windows.iter().map(|(_, w)| w.memory_usage()).sum();
+		let buffers: usize = self.buffers.iter().map(|b| b.capacity()).sum();
+		let buffers = buffers + self.buffers.capacity() * size_of::<Vec<u8>>();
+		// hash tables are estimated from their capacity, without their control bytes
+		let dirty = self.dirty.capacity() * size_of::<Rect>()
+			+ self.frozen.capacity() * size_of::<WindowId>()
+			+ self.fill_colors.capacity() * size_of::<(WindowId, (u8,u8,u8))>();
+		size_of::<FBmanager>() + self.buffer.capacity() + self.frame.capacity() + self.scratch.capacity() + buffers + dirty + store + windows
+	}
+	/// Gets the `PixelFormat` used when drawing to the screen
+	pub fn pixel_format(&self) -> PixelFormat {
+		self.pixel_format
+	}
+	/// Overrides the detected `PixelFormat`, for drivers that report wrong channel offsets
+	///
+	/// Returns `FBmanagerError::OutOfBounds` if a channel offset doesn't fit in a pixel,
+	/// or `FBmanagerError::InvalidPixelFormat` if two channels share an offset
+	pub fn set_pixel_format(&mut self, format: PixelFormat) -> Result<(), FBmanagerError> {
+		format.check(self.bytes_per_pixel())?;
+		self.pixel_format = format;
+		Ok(())
+	}
+	/// Checks if `framebuffer` is laid out in linear scanlines of packed pixels, the only layout an `FBmanager` can draw to
+	///
+	/// Creating an `FBmanager` for any other layout fails with `FBmanagerError::UnsupportedLayout`
+	pub fn is_linear_framebuffer(framebuffer: &Framebuffer) -> bool {
+		framebuffer.fix_screen_info.fb_type == FB_TYPE_PACKED_PIXELS
+	}
+	/// Checks if the framebuffer is laid out in linear scanlines of packed pixels
+	///
+	/// Always true, as creating an `FBmanager` fails otherwise. Use `is_linear_framebuffer` to check a device beforehand
+	pub fn is_linear(&self) -> bool {
+		FBmanager::is_linear_framebuffer(&self.framebuffer)
+	}
+	/// Gets the identifier the driver reports for the device, e.g. `"simple"` or `"vc4drmfb"`
+	pub fn device_id(&self) -> String {
+		let id = &self.framebuffer.fix_screen_info.id;
+		let len = id.iter().position(|&c| c == 0).unwrap_or(id.len());
+		String::from_utf8_lossy(&id[..len]).into_owned()
+	}
+	/// Computes the display's refresh rate from the timings in `var_screen_info` like `fbset` does
+	///
+	/// Returns `None` if the driver doesn't report a pixel clock
+	pub fn refresh_rate_hz(&self) -> Option<f32> {
+		refresh_rate_hz(&self.framebuffer.var_screen_info)
+	}
+	/// Rotates the contents of the buffer by 180° so the next `draw` shows them upside-down
+	///
+	/// For a cropped `FBmanager` only the area covered by the buffer is rotated
+	pub fn rotate_buffer_180(&mut self) {
+		self.mark_dirty(self.bounds);
+		rotate_180(&mut self.buffer, &self.layout, self.bounds.width, self.bounds.height);
+	}
+	/// Computes a 64-bit FNV-1a hash of the buffer, e.g. to detect changes between frames
+	pub fn buffer_hash(&self) -> u64 {
+		fnv1a(&self.buffer)
+	}
+	/// Writes the buffer to the file at `path` with a header of the covered area of the screen, bits per pixel and line length
+	pub fn dump_raw<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+		let mut file = File::create(path)?;
+		file.write_all(&self.raw_header())?;
+		file.write_all(&self.buffer)
+	}
+	/// Loads a buffer written by `dump_raw` from the file at `path`
+	///
+	/// Fails with `io::ErrorKind::InvalidData` if the dump was made for a screen with different geometry
+	pub fn load_raw<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
+		let mut file = File::open(path)?;
+		self.buffer = read_raw(&mut file, &self.raw_header(), self.buffer.len())?;
+		self.mark_dirty(self.bounds);
+		Ok(())
+	}
+	/// Builds the header written by `dump_raw`
+	fn raw_header(&self) -> [u8; RAW_HEADER_LEN] {
+		raw_header(self.bounds, self.framebuffer.var_screen_info.bits_per_pixel as usize, self.layout.stride)
+	}
+	/// Gets the number of bits of red, green and blue the device displays
+	pub fn channel_bits(&self) -> (u8,u8,u8) {
+		let info = &self.framebuffer.var_screen_info;
+		let bits = |length: u32| if length == 0 { 8 } else { length.min(8) as u8 };
+		(bits(info.red.length), bits(info.green.length), bits(info.blue.length))
+	}
+	/// Gets the number of bytes per pixel of the buffer
+	fn bytes_per_pixel(&self) -> usize {
+		(self.framebuffer.var_screen_info.bits_per_pixel / 8) as usize
 	}
 	/// Draws the `FBmanager`'s internal state to the screen. Remeber to `enable_graphics()` before this
 	pub fn draw(&mut self) {
-		self.framebuffer.write_frame( &self.buffer);
+		self.last_draw = Some(Instant::now());
+		#[cfg(feature = "remote")]
+		self.send_remote();
+		self.dirty.clear();
+		write_frame(&mut self.framebuffer, &mut self.frame, &self.buffer, self.pixel_format, &self.layout, self.bounds);
+	}
+	/// Draws only the bounding box of the dirty regions to the screen, in a single pass. Does nothing if nothing is dirty
+	///
+	/// Only areas changed through the `FBmanager` or recorded with `mark_dirty` are written
+	pub fn draw_dirty(&mut self) {
+		let region = match self.dirty_bounds() {
+			Some(region) => region,
+			None => return,
+		};
+		self.last_draw = Some(Instant::now());
+		#[cfg(feature = "remote")]
+		self.send_remote();
+		self.dirty.clear();
+		write_frame(&mut self.framebuffer, &mut self.frame, &self.buffer, self.pixel_format, &self.layout, region);
+	}
+	/// Creates a new black buffer with the screen's geometry to render into, for presenting later with `present`
+	pub fn create_buffer(&mut self) -> BufferId {
+		self.buffers.push(vec![0u8; self.buffer.len()]);
+		BufferId(self.buffers.len() - 1)
+	}
+	/// Gets the `WindowStore` together with the buffer `target`, for drawing into it with `Window` methods
+	pub fn render_target(&mut self, target: BufferId) -> (&WindowStore, &mut [u8]) {
+		(&self.windows, &mut self.buffers[target.0])
+	}
+	/// Fills the `Window` with the given `id` in the buffer `target` to the given color
+	pub fn fill_target(&mut self, target: BufferId, id: WindowId, rgb: (u8,u8,u8)) {
+		if self.is_window_frozen(id) {
+			return;
+		}
+		self.windows[id].fill(&mut self.buffers[target.0], rgb, self.global_alpha);
+	}
+	/// Fills the `Window` with the given `id`'s border in the buffer `target` to the given color
+	pub fn fill_border_target(&mut self, target: BufferId, id: WindowId, rgb: (u8,u8,u8)) {
+		if self.is_window_frozen(id) {
+			return;
+		}
+		self.windows[id].fill_border(&mut self.buffers[target.0], rgb, self.global_alpha);
+	}
+	/// Draws the buffer `target` to the screen. The internal state drawn by `draw` is not changed
+	pub fn present(&mut self, target: BufferId) {
+		self.last_draw = Some(Instant::now());
+		write_frame(&mut self.framebuffer, &mut self.frame, &self.buffers[target.0], self.pixel_format, &self.layout, self.bounds);
+	}
+	/// Shows the `Window` with the given `id` sliding in from the `from` edge of the area covered by the buffer, `progress` of the way (0.0 to 1.0) to its place
+	///
+	/// The `Window`'s contents in the buffer are shown shifted and clipped to the area covered by the buffer, and the strip it slid across is restored from the buffer.
+	/// Only that path is written to the screen and the buffer isn't changed, so `draw` the background before drawing the `Window`, then call this with increasing `progress` each frame
+	pub fn slide_in(&mut self, id: WindowId, from: Edge, progress: f32) {
+		let window = self.windows[id].rect();
+		let layout = self.layout;
+		let bytespp = layout.bytespp;
+		let (left, top) = (self.bounds.x as isize, self.bounds.y as isize);
+		let (right, bottom) = (left + self.bounds.width as isize, top + self.bounds.height as isize);
+		let (x, y) = (window.x as isize, window.y as isize);
+		let (w, h) = (window.width as isize, window.height as isize);
+		let remaining = 1.0 - progress.clamp(0.0, 1.0);
+		let travel = |distance: isize| (distance as f32 * remaining).round() as isize;
+		// at 0.0 the window is just past the edge
+		let (dx, dy) = match from {
+			Edge::Left => (-travel(x + w - left), 0),
+			Edge::Right => (travel(right - x), 0),
+			Edge::Top => (0, -travel(y + h - top)),
+			Edge::Bottom => (0, travel(bottom - y)),
+		};
+		let clip = |x0: isize, y0: isize, x1: isize, y1: isize| {
+			let (x0, x1) = (x0.clamp(left, right), x1.clamp(left, right));
+			let (y0, y1) = (y0.clamp(top, bottom), y1.clamp(top, bottom));
+			Rect::new(x0 as usize, y0 as usize, (x1 - x0).max(0) as usize, (y1 - y0).max(0) as usize)
+		};
+		let (sx, sy) = (x + dx, y + dy);
+		let shifted = clip(sx, sy, sx + w, sy + h);
+		// the window came from the edge, so everything between it and the edge is outside of its place
+		let strip = match from {
+			Edge::Left => clip(left, y, sx, y + h),
+			Edge::Right => clip(sx + w, y, right, y + h),
+			Edge::Top => clip(x, top, x + w, sy),
+			Edge::Bottom => clip(x, sy + h, x + w, bottom),
+		};
+		// only the shifted area is written from the scratch buffer, so the rest of it may be stale
+		self.scratch.resize(self.buffer.len(), 0);
+		for py in shifted.y..shifted.y + shifted.height {
+			for px in shifted.x..shifted.x + shifted.width {
+				let src = layout.index((px as isize - dx) as usize, (py as isize - dy) as usize);
+				let dst = layout.index(px, py);
+				self.scratch[dst..dst + bytespp].copy_from_slice(&self.buffer[src..src + bytespp]);
+			}
+		}
+		self.last_draw = Some(Instant::now());
+		write_frame(&mut self.framebuffer, &mut self.frame, &self.scratch, self.pixel_format, &layout, shifted);
+		write_frame(&mut self.framebuffer, &mut self.frame, &self.buffer, self.pixel_format, &layout, strip);
+	}
+	/// Draws to the screen only if at least `min_interval` has passed since the last draw. Returns whether it drew
+	///
+	/// Skipped updates stay in the buffer and are shown by the next draw
+	pub fn draw_throttled(&mut self, min_interval: Duration) -> bool {
+		if let Some(last) = self.last_draw {
+			if last.elapsed() < min_interval {
+				return false;
+			}
+		}
+		self.draw();
+		true
 	}
 }
 
@@ -248,6 +1789,19 @@ impl FBmanager {
 mod tests {
 	use super::*;
 
+	/// Builds a border-less `Window` at the origin of a buffer exactly its size, with that black buffer
+	pub(crate) fn test_window(width: usize, height: usize, bytespp: usize) -> (Window, Vec<u8>) {
+		let template = WindowTemplate {
+			id: 0,
+			location: Point::new(0,0),
+			width,
+			height,
+			border_thickness: 0,
+		};
+		let window = Window::from_geometry(&template, &Layout::new(width * bytespp, bytespp));
+		(window, vec![0u8; width * height * bytespp])
+	}
+
 	#[test]
 	fn single_square() {
 		let square = WindowTemplate {
@@ -257,10 +1811,9 @@ mod tests {
 			height: 1000,
 			border_thickness: 0,
 		};
-		let mut fm = FBmanager::new(&[square]);
-		let mut win = &mut fm.windows[0];
-		fm.windows[0].main_context.pixels[0][0].set_rgb(&mut fm.buffer,255,0,0);
-		fm.fill(0,(255,0,0));
+		let mut fm = FBmanager::new(&[square]).unwrap();
+		fm.windows[0.into()].main_context.pixels[0][0].set_rgb(&mut fm.buffer,255,0,0);
+		fm.fill(0.into(),(255,0,0));
 		let step_size = (fm.framebuffer.var_screen_info.bits_per_pixel / 8) as usize;
 		let line_length: usize = fm.framebuffer.fix_screen_info.line_length as usize;
 		println!("{:?}", fm.buffer);
@@ -273,4 +1826,362 @@ mod tests {
 			}
 		}
 	}
+
+	#[test]
+	fn disarmed_clear_guard() {
+		let path = std::env::temp_dir().join(format!("fbmanager-clear-guard-{}", std::process::id()));
+		std::fs::write(&path, [0xffu8; 16]).unwrap();
+		let guard = || ClearGuard {
+			device: std::fs::OpenOptions::new().write(true).open(&path).unwrap(),
+			line_length: 8,
+			bytespp: 2,
+			bounds: Rect::new(1, 0, 2, 2),
+		};
+		guard().disarm();
+		assert_eq!(std::fs::read(&path).unwrap(), vec![0xffu8; 16]);
+		drop(guard());
+		let cleared = std::fs::read(&path).unwrap();
+		std::fs::remove_file(&path).unwrap();
+		assert_eq!(&cleared[..8], &[0xff, 0xff, 0, 0, 0, 0, 0xff, 0xff]);
+		assert_eq!(&cleared[8..], &[0xff, 0xff, 0, 0, 0, 0, 0xff, 0xff]);
+	}
+
+	#[test]
+	fn blend_pixel() {
+		let mut buffer = vec![0u8; 3];
+		let pixel = Pixel{index: 0};
+		pixel.set_rgb(&mut buffer, 200, 100, 0);
+		pixel.blend_rgb(&mut buffer, 0, 0, 0, 0.5);
+		assert_eq!(pixel.get_rgb(&buffer), (100, 50, 0));
+		pixel.blend_rgb(&mut buffer, 255, 255, 255, 0.0);
+		assert_eq!(pixel.get_rgb(&buffer), (100, 50, 0));
+	}
+
+	#[test]
+	fn stable_window_ids() {
+		let template = WindowTemplate {
+			id: 0,
+			location: Point::new(0,0),
+			width: 4,
+			height: 4,
+			border_thickness: 1,
+		};
+		let mut store = WindowStore::default();
+		let a = store.insert(Window::from_geometry(&template, &Layout::new(16, 4)));
+		let b = store.insert(Window::from_geometry(&template, &Layout::new(16, 4)));
+		assert_eq!(a, WindowId::from(0));
+		assert!(store.remove(a).is_some());
+		assert!(store.remove(a).is_none());
+		let c = store.insert(Window::from_geometry(&template, &Layout::new(16, 4)));
+		assert_ne!(a, c);
+		assert!(!store.contains(a));
+		assert!(store.contains(b));
+		assert!(store.contains(c));
+		assert_eq!(store.len(), 2);
+	}
+
+	#[test]
+	fn z_order_survives_slot_reuse() {
+		let template = WindowTemplate {
+			id: 0,
+			location: Point::new(0,0),
+			width: 4,
+			height: 4,
+			border_thickness: 1,
+		};
+		let mut store = WindowStore::default();
+		let a = store.insert(Window::from_geometry(&template, &Layout::new(16, 4)));
+		let b = store.insert(Window::from_geometry(&template, &Layout::new(16, 4)));
+		store.remove(a);
+		let c = store.insert(Window::from_geometry(&template, &Layout::new(16, 4)));
+		let order: Vec<_> = store.by_z().into_iter().map(|(id, _)| id).collect();
+		assert_eq!(order, vec![b, c]);
+		store[b].z_index = 1;
+		let order: Vec<_> = store.by_z().into_iter().map(|(id, _)| id).collect();
+		assert_eq!(order, vec![c, b]);
+	}
+
+	#[test]
+	fn conic_gradient() {
+		let (window, mut buffer) = test_window(3, 3, 3);
+		window.fill_gradient_conic(&mut buffer, Point::new(1,1), 0.0, &[(255,0,0),(0,0,255)]);
+		assert_eq!(window.main_context.pixels[1][2].get_rgb(&buffer), (255,0,0));
+		assert_eq!(window.main_context.pixels[1][0].get_rgb(&buffer), (0,0,255));
+	}
+
+	#[test]
+	fn detect_pixel_format() {
+		let mut info = VarScreeninfo::default();
+		assert_eq!(PixelFormat::detect(&info), PixelFormat::BGR);
+		info.bits_per_pixel = 24;
+		info.red.offset = 0;
+		info.red.length = 8;
+		info.green.offset = 8;
+		info.green.length = 8;
+		info.blue.offset = 16;
+		info.blue.length = 8;
+		assert_eq!(PixelFormat::detect(&info), PixelFormat::RGB);
+		// red past the end of a 24 bit pixel
+		info.red.offset = 24;
+		assert_eq!(PixelFormat::detect(&info), PixelFormat::BGR);
+		// red and green in the same byte
+		info.red.offset = 8;
+		assert_eq!(PixelFormat::detect(&info), PixelFormat::BGR);
+	}
+
+	#[test]
+	fn check_pixel_format() {
+		assert!(PixelFormat::BGR.check(3).is_ok());
+		assert!(PixelFormat {red: 3, green: 1, blue: 0}.check(4).is_ok());
+		assert!(matches!(PixelFormat {red: 3, green: 1, blue: 0}.check(3), Err(FBmanagerError::OutOfBounds)));
+		assert!(matches!(PixelFormat {red: 0, green: 0, blue: 2}.check(3), Err(FBmanagerError::InvalidPixelFormat)));
+	}
+
+	#[test]
+	fn refresh_rate_timings() {
+		// VESA 640x480 at 60 Hz
+		let mut info = VarScreeninfo::default();
+		assert_eq!(refresh_rate_hz(&info), None);
+		info.xres = 640;
+		info.left_margin = 48;
+		info.right_margin = 16;
+		info.hsync_len = 96;
+		info.yres = 480;
+		info.upper_margin = 33;
+		info.lower_margin = 10;
+		info.vsync_len = 2;
+		info.pixclock = 39721;
+		let hz = refresh_rate_hz(&info).unwrap();
+		assert!((hz - 59.94).abs() < 0.01);
+		info.vmode = 1;
+		assert!((refresh_rate_hz(&info).unwrap() - 2.0 * hz).abs() < 0.01);
+		info.vmode = 2;
+		assert!((refresh_rate_hz(&info).unwrap() - hz / 2.0).abs() < 0.01);
+		info.pixclock = 0;
+		assert_eq!(refresh_rate_hz(&info), None);
+	}
+
+	#[test]
+	fn raw_dump_header() {
+		let header = raw_header(Rect::new(0, 0, 2, 1), 32, 8);
+		let mut dump = header.to_vec();
+		dump.extend_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]);
+		assert_eq!(read_raw(&mut &dump[..], &header, 8).unwrap(), &dump[RAW_HEADER_LEN..]);
+		let other = raw_header(Rect::new(0, 0, 2, 1), 16, 8);
+		assert_eq!(read_raw(&mut &dump[..], &other, 8).unwrap_err().kind(), io::ErrorKind::InvalidData);
+		assert_eq!(read_raw(&mut &dump[..dump.len() - 1], &header, 8).unwrap_err().kind(), io::ErrorKind::InvalidData);
+		dump.push(9);
+		assert_eq!(read_raw(&mut &dump[..], &header, 8).unwrap_err().kind(), io::ErrorKind::InvalidData);
+	}
+
+	#[test]
+	fn rotate_padded_buffer() {
+		// 3x3 pixels of 2 bytes in lines of 8 bytes, the pixels numbered 1 to 9 and the padding 0xff
+		let layout = Layout::new(8, 2);
+		let mut buffer = Vec::new();
+		for y in 0..3 {
+			for x in 0..3 {
+				buffer.extend_from_slice(&[y * 3 + x + 1, 0]);
+			}
+			buffer.extend_from_slice(&[0xff, 0xff]);
+		}
+		rotate_180(&mut buffer, &layout, 3, 3);
+		let pixels: Vec<u8> = (0..9).map(|k| buffer[layout.index(k % 3, k / 3)]).collect();
+		assert_eq!(pixels, [9, 8, 7, 6, 5, 4, 3, 2, 1]);
+		for y in 0..3 {
+			assert_eq!(&buffer[y * 8 + 6..y * 8 + 8], &[0xff, 0xff]);
+		}
+	}
+
+	#[test]
+	fn rect_bounds() {
+		let a = Rect::new(0, 10, 100, 50);
+		let b = Rect::new(50, 40, 100, 100);
+		assert_eq!(a.union(&b), Rect::new(0, 10, 150, 130));
+		assert_eq!(a.intersection(&b), Rect::new(50, 40, 50, 20));
+		assert_eq!(a.intersection(&Rect::new(200, 200, 5, 5)).width, 0);
+		assert!(a.union(&b).contains(&b));
+		assert!(!a.contains(&b));
+	}
+
+	#[test]
+	fn cropped_layout() {
+		let template = WindowTemplate {
+			id: 0,
+			location: Point::new(2,3),
+			width: 2,
+			height: 2,
+			border_thickness: 0,
+		};
+		let layout = Layout {origin: Point::new(2,3), stride: 8, bytespp: 4};
+		let window = Window::from_geometry(&template, &layout);
+		assert_eq!(window.main_context.pixels[0][0].index, 0);
+		assert_eq!(window.main_context.pixels[1][1].index, 12);
+	}
+
+	#[test]
+	fn sparkline_scales_to_context() {
+		let (window, mut buffer) = test_window(3, 3, 3);
+		window.draw_sparkline(&mut buffer, &[0.0, 10.0], (255,255,255));
+		let rows = &window.main_context.pixels;
+		assert_eq!(rows[2][0].get_rgb(&buffer), (255,255,255));
+		assert_eq!(rows[1][1].get_rgb(&buffer), (255,255,255));
+		assert_eq!(rows[0][2].get_rgb(&buffer), (255,255,255));
+		assert_eq!(rows[0][0].get_rgb(&buffer), (0,0,0));
+	}
+
+	#[test]
+	fn merge_dirty_regions() {
+		let mut regions = Vec::new();
+		add_region(&mut regions, Rect::new(0, 0, 10, 10));
+		add_region(&mut regions, Rect::new(20, 20, 10, 10));
+		assert_eq!(regions.len(), 2);
+		// bridges both regions
+		add_region(&mut regions, Rect::new(5, 5, 20, 20));
+		assert_eq!(regions, vec![Rect::new(0, 0, 30, 30)]);
+	}
+
+	#[test]
+	fn arrow_shape() {
+		let (window, mut buffer) = test_window(10, 10, 3);
+		window.fill_arrow(&mut buffer, Rect::new(0, 0, 10, 10), Direction::Right, (255,255,255));
+		let rows = &window.main_context.pixels;
+		assert_eq!(rows[5][1].get_rgb(&buffer), (255,255,255));
+		assert_eq!(rows[6][2].get_rgb(&buffer), (255,255,255));
+		assert_eq!(rows[1][1].get_rgb(&buffer), (0,0,0));
+		assert_eq!(rows[0][9].get_rgb(&buffer), (0,0,0));
+	}
+
+	#[test]
+	fn rounded_corners() {
+		let (window, mut buffer) = test_window(10, 10, 3);
+		buffer.fill(255);
+		window.round_corners(&mut buffer, Rect::new(0, 0, 10, 10), 4, (0,0,0));
+		let rows = &window.main_context.pixels;
+		assert_eq!(rows[0][0].get_rgb(&buffer), (0,0,0));
+		assert_eq!(rows[9][9].get_rgb(&buffer), (0,0,0));
+		assert_eq!(rows[0][4].get_rgb(&buffer), (255,255,255));
+		assert_eq!(rows[2][2].get_rgb(&buffer), (255,255,255));
+		assert_eq!(rows[5][0].get_rgb(&buffer), (255,255,255));
+	}
+
+	#[test]
+	fn pixel_view_bounds() {
+		let template = WindowTemplate {
+			id: 0,
+			location: Point::new(2,1),
+			width: 4,
+			height: 3,
+			border_thickness: 0,
+		};
+		let window = Window::from_geometry(&template, &Layout::new(24, 3));
+		let view = window.pixels_2d();
+		assert_eq!((view.width(), view.height()), (4, 3));
+		assert_eq!(view.get(3, 2).map(|p| p.index), Some(3 * 24 + 5 * 3));
+		assert!(view.get(4, 0).is_none());
+		assert!(window.pixel_at(0, 3).is_none());
+		assert_eq!(window.pixel_at(0, 0).map(|p| p.index), Some(24 + 2 * 3));
+	}
+
+	#[test]
+	fn fit_content() {
+		let (window, _) = test_window(100, 50, 3);
+		assert_eq!(window.aspect_ratio(), 2.0);
+		assert_eq!(window.fit_rect(200, 200, FitMode::Contain), (Point::new(25,0), 50, 50));
+		assert_eq!(window.fit_rect(200, 200, FitMode::Cover), (Point::new(0,0), 100, 50));
+		assert_eq!(window.fit_rect(0, 200, FitMode::Contain), (Point::new(50,25), 0, 0));
+		assert_eq!(window.cover_crop(200, 200), Rect::new(0, 50, 200, 100));
+	}
+
+	#[test]
+	fn indexed_fill() {
+		let (window, mut buffer) = test_window(2, 1, 3);
+		let mut palette = [(0u8,0u8,0u8); 256];
+		palette[1] = (9,8,7);
+		assert!(matches!(window.fill_indexed(&mut buffer, &[1], &palette), Err(FBmanagerError::SizeMismatch)));
+		assert!(buffer.iter().all(|&b| b == 0));
+		window.fill_indexed(&mut buffer, &[0, 1], &palette).unwrap();
+		assert_eq!(window.main_context.pixels[0][1].get_rgb(&buffer), (9,8,7));
+	}
+
+	#[test]
+	fn threshold_level_is_above() {
+		let (window, mut buffer) = test_window(2, 1, 3);
+		let rows = &window.main_context.pixels;
+		rows[0][0].set_rgb(&mut buffer, 99, 99, 99);
+		rows[0][1].set_rgb(&mut buffer, 100, 100, 100);
+		window.threshold(&mut buffer, 100, (0,0,0), (255,255,255));
+		assert_eq!(rows[0][0].get_rgb(&buffer), (0,0,0));
+		assert_eq!(rows[0][1].get_rgb(&buffer), (255,255,255));
+	}
+
+	#[test]
+	fn replace_color_tolerance() {
+		let (window, mut buffer) = test_window(3, 1, 3);
+		let rows = &window.main_context.pixels;
+		rows[0][0].set_rgb(&mut buffer, 105, 95, 100);
+		rows[0][1].set_rgb(&mut buffer, 106, 100, 100);
+		rows[0][2].set_rgb(&mut buffer, 100, 100, 94);
+		window.replace_color(&mut buffer, (100,100,100), (1,2,3), 5);
+		assert_eq!(rows[0][0].get_rgb(&buffer), (1,2,3));
+		assert_eq!(rows[0][1].get_rgb(&buffer), (106,100,100));
+		assert_eq!(rows[0][2].get_rgb(&buffer), (100,100,94));
+	}
+
+	#[test]
+	fn noise_is_deterministic() {
+		let (window, mut a) = test_window(8, 8, 3);
+		let mut b = a.clone();
+		let mut c = a.clone();
+		window.fill_noise(&mut a, 7, 2.0, (0,0,0), (255,255,255));
+		window.fill_noise(&mut b, 7, 2.0, (0,0,0), (255,255,255));
+		window.fill_noise(&mut c, 8, 2.0, (0,0,0), (255,255,255));
+		assert_eq!(a, b);
+		assert_ne!(a, c);
+	}
+
+	#[test]
+	fn histogram_bars() {
+		let (window, mut buffer) = test_window(4, 4, 3);
+		window.draw_histogram(&mut buffer, &[1, 2], (255,255,255));
+		let rows = &window.main_context.pixels;
+		assert_eq!(rows[3][0].get_rgb(&buffer), (255,255,255));
+		assert_eq!(rows[1][0].get_rgb(&buffer), (0,0,0));
+		assert_eq!(rows[0][2].get_rgb(&buffer), (255,255,255));
+		// a column of space between bars
+		assert_eq!(rows[3][1].get_rgb(&buffer), (0,0,0));
+		assert_eq!(rows[3][3].get_rgb(&buffer), (0,0,0));
+	}
+
+	#[test]
+	fn column_phase() {
+		let (window, mut buffer) = test_window(6, 1, 3);
+		window.fill_columns(&mut buffer, 3, 1, (255,255,255));
+		let lit: Vec<_> = window.main_context.pixels[0].iter().map(|p| p.get_rgb(&buffer) != (0,0,0)).collect();
+		assert_eq!(lit, vec![false, false, true, false, false, true]);
+		let (window, mut buffer) = test_window(6, 1, 3);
+		window.fill_columns(&mut buffer, 3, 0, (255,255,255));
+		let lit: Vec<_> = window.main_context.pixels[0].iter().map(|p| p.get_rgb(&buffer) != (0,0,0)).collect();
+		assert_eq!(lit, vec![true, false, false, true, false, false]);
+	}
+
+	#[test]
+	fn stripe_phase() {
+		let (a, b) = ((1,1,1), (2,2,2));
+		let (window, mut buffer) = test_window(5, 2, 3);
+		window.fill_stripes(&mut buffer, 2, StripeAngle::Deg45, a, b);
+		let rows = &window.main_context.pixels;
+		let colors: Vec<_> = rows[0].iter().map(|p| p.get_rgb(&buffer)).collect();
+		assert_eq!(colors, vec![a, a, b, b, a]);
+		assert_eq!(rows[1][1].get_rgb(&buffer), b);
+		window.fill_stripes(&mut buffer, 2, StripeAngle::NegDeg45, a, b);
+		assert_eq!(rows[1][0].get_rgb(&buffer), b);
+		assert_eq!(rows[1][1].get_rgb(&buffer), a);
+	}
+
+	#[test]
+	fn fnv1a_hash() {
+		assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+		assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+	}
 }
@@ -43,12 +43,12 @@ fn main() {
 		border_thickness: 5,
 	};
 	template.push(bargraph);
-	let mut fm = FBmanager::new(&template);
-	fm.fill(1,(0,255,0));
-	fm.fill(2,(0,0,255));
-	fm.fill(3,(255,255,0));
-	fm.fill(4,(0,255,255));
-	fm.fill_border(0,(255,0,0));
+	let mut fm = FBmanager::new(&template).unwrap();
+	fm.fill(1.into(),(0,255,0));
+	fm.fill(2.into(),(0,0,255));
+	fm.fill(3.into(),(255,255,0));
+	fm.fill(4.into(),(0,255,255));
+	fm.fill_border(0.into(),(255,0,0));
 	framebuffer_manager::FBmanager::enable_graphics().unwrap();
 	fm.draw();
 	io::stdin().read_line(&mut String::new()).unwrap_or_default();
@@ -0,0 +1,32 @@
+//! QR code rendering, enabled with the `qr` feature
+
+use crate::{Point, Rect, Window};
+use qrcode::{Color, QrCode, QrResult};
+
+impl Window {
+	/// Draws a QR code encoding `data` with its top left corner at the window-relative `origin`
+	///
+	/// Each module is a `module_size` square. The code is surrounded by a quiet zone in `bg_rgb` as required for scanning,
+	/// which is included in `origin`. The part outside of the `main_context` is clipped
+	pub fn draw_qr(&self, buffer: &mut [u8], origin: Point, data: &str, module_size: usize, fg_rgb: (u8,u8,u8), bg_rgb: (u8,u8,u8)) -> QrResult<()> {
+		let code = QrCode::new(data.as_bytes())?;
+		let quiet_zone = if code.version().is_micro() { 2 } else { 4 };
+		let width = code.width();
+		let size = (width + 2 * quiet_zone) * module_size;
+		self.fill_rect(buffer, Rect::new(origin.x, origin.y, size, size), bg_rgb);
+		for y in 0..width {
+			for x in 0..width {
+				if code[(x, y)] == Color::Dark {
+					let module = Rect::new(
+						origin.x + (x + quiet_zone) * module_size,
+						origin.y + (y + quiet_zone) * module_size,
+						module_size,
+						module_size,
+					);
+					self.fill_rect(buffer, module, fg_rgb);
+				}
+			}
+		}
+		Ok(())
+	}
+}
@@ -0,0 +1,115 @@
+//! Mirroring of dirty regions to a remote display, enabled with the `remote` feature
+//!
+//! Each `draw` sends one message:
+//! a little-endian `u32` region count, then for each region its `x`, `y`, `width` and `height` as little-endian `u32`s
+//! followed by `width * height` RGB pixels, row-major.
+//!
+//! The whole area covered by the buffer is marked dirty when a sink is set, so the first message is a full frame.
+
+use crate::{FBmanager, Layout, Rect};
+use std::io;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+
+impl FBmanager {
+	/// Sends the dirty regions to `sink` on each `draw` from now on. The sink is dropped when sending fails
+	///
+	/// Marks the whole buffer dirty so the next `draw` brings the peer in sync
+	pub fn set_remote_sink(&mut self, sink: TcpStream) {
+		self.remote = Some(sink);
+		self.mark_dirty(self.bounds);
+	}
+	/// Stops sending to the remote sink and gives it back
+	pub fn take_remote_sink(&mut self) -> Option<TcpStream> {
+		self.remote.take()
+	}
+	/// Sends the current dirty regions to the remote sink, if there is one
+	pub(crate) fn send_remote(&mut self) {
+		if let Some(sink) = &mut self.remote {
+			let message = encode_regions(&self.buffer, &self.layout, &self.dirty);
+			if sink.write_all(&message).is_err() {
+				self.remote = None;
+			}
+		}
+	}
+}
+
+/// Encodes `regions` of `buffer` into a message
+fn encode_regions(buffer: &[u8], layout: &Layout, regions: &[Rect]) -> Vec<u8> {
+	let pixels: usize = regions.iter().map(|r| r.width * r.height).sum();
+	let mut message = Vec::with_capacity(4 + regions.len() * 16 + pixels * 3);
+	message.extend_from_slice(&(regions.len() as u32).to_le_bytes());
+	for r in regions {
+		for field in [r.x, r.y, r.width, r.height].iter() {
+			message.extend_from_slice(&(*field as u32).to_le_bytes());
+		}
+		for y in r.y..r.y + r.height {
+			for x in r.x..r.x + r.width {
+				let i = layout.index(x, y);
+				message.extend_from_slice(&[buffer[i+2], buffer[i+1], buffer[i]]);
+			}
+		}
+	}
+	message
+}
+
+/// Rebuilds the screen of a remote `FBmanager` from the messages it sends
+pub struct RemoteReceiver {
+	pub width: usize,
+	pub height: usize,
+	/// The screen as RGB pixels, row-major
+	pub buffer: Vec<u8>,
+}
+
+impl RemoteReceiver {
+	/// Creates a new `RemoteReceiver` with a black screen of the given size
+	pub fn new(width: usize, height: usize) -> Self {
+		RemoteReceiver {width, height, buffer: vec![0u8; width * height * 3]}
+	}
+	/// Reads one message from `reader` and applies it to the `buffer`. Returns the updated regions
+	///
+	/// Fails with `io::ErrorKind::InvalidData` if a region doesn't fit on the screen
+	pub fn receive<R: Read>(&mut self, reader: &mut R) -> io::Result<Vec<Rect>> {
+		let count = read_u32(reader)?;
+		let screen = Rect::new(0, 0, self.width, self.height);
+		let mut regions = Vec::new();
+		for _ in 0..count {
+			let r = Rect::new(read_u32(reader)?, read_u32(reader)?, read_u32(reader)?, read_u32(reader)?);
+			if !screen.contains(&r) {
+				return Err(io::Error::new(io::ErrorKind::InvalidData, "region doesn't fit on the screen"));
+			}
+			for y in r.y..r.y + r.height {
+				let start = (y * self.width + r.x) * 3;
+				reader.read_exact(&mut self.buffer[start..start + r.width * 3])?;
+			}
+			regions.push(r);
+		}
+		Ok(regions)
+	}
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<usize> {
+	let mut bytes = [0u8; 4];
+	reader.read_exact(&mut bytes)?;
+	Ok(u32::from_le_bytes(bytes) as usize)
+}
+
+//tests
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn round_trip() {
+		let layout = Layout::new(8, 4);
+		let mut buffer = vec![0u8; 16];
+		// BGR at (1, 1)
+		buffer[12..15].copy_from_slice(&[3, 2, 1]);
+		let message = encode_regions(&buffer, &layout, &[Rect::new(1, 0, 1, 2)]);
+		let mut receiver = RemoteReceiver::new(2, 2);
+		let regions = receiver.receive(&mut &message[..]).unwrap();
+		assert_eq!(regions, vec![Rect::new(1, 0, 1, 2)]);
+		assert_eq!(&receiver.buffer[9..12], &[1, 2, 3]);
+	}
+}
@@ -0,0 +1,36 @@
+//! PNG export of the buffer, enabled with the `png` feature
+
+use crate::{FBmanager, Rect};
+use std::fs::File;
+use std::io;
+use std::io::BufWriter;
+use std::path::Path;
+
+impl FBmanager {
+	/// Saves the area of the screen covered by the buffer as a PNG image at `path`
+	pub fn save_png<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+		self.save_png_region(self.bounds, path)
+	}
+	/// Saves the `rect` of the screen as a PNG image at `path`
+	///
+	/// Fails with `io::ErrorKind::InvalidInput` if `rect` is empty or not inside of the area covered by the buffer
+	pub fn save_png_region<P: AsRef<Path>>(&self, rect: Rect, path: P) -> io::Result<()> {
+		if rect.width == 0 || rect.height == 0 || !self.bounds.contains(&rect) {
+			return Err(io::Error::new(io::ErrorKind::InvalidInput, "region is outside of the screen"));
+		}
+		let mut rgb = Vec::with_capacity(rect.width * rect.height * 3);
+		for y in rect.y..rect.y + rect.height {
+			for x in rect.x..rect.x + rect.width {
+				let i = self.layout.index(x, y);
+				rgb.extend_from_slice(&[self.buffer[i+2], self.buffer[i+1], self.buffer[i]]);
+			}
+		}
+		let file = BufWriter::new(File::create(path)?);
+		let mut encoder = png::Encoder::new(file, rect.width as u32, rect.height as u32);
+		encoder.set_color(png::ColorType::Rgb);
+		encoder.set_depth(png::BitDepth::Eight);
+		let mut writer = encoder.write_header()?;
+		writer.write_image_data(&rgb)?;
+		Ok(())
+	}
+}
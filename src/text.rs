@@ -0,0 +1,250 @@
+//! TrueType text rendering, enabled with the `ttf` feature
+
+use crate::{FBmanager, Point, Window, WindowId};
+use fontdue::Metrics;
+pub use fontdue::{Font, FontSettings};
+use std::collections::HashMap;
+
+/// Measures the `(width, height)` in pixels of `text` rendered with `font` at `size`
+pub fn measure_text(font: &Font, text: &str, size: f32) -> (usize, usize) {
+	let width: f32 = text.chars().map(|c| font.metrics(c, size).advance_width).sum();
+	let height = match font.horizontal_line_metrics(size) {
+		Some(lm) => lm.ascent - lm.descent,
+		None => size,
+	};
+	(width.ceil() as usize, height.ceil() as usize)
+}
+
+/// Caches rasterized glyphs of a `Font` so text that changes often doesn't rasterize the same glyphs again
+///
+/// Glyphs are kept for every size they were drawn at until `clear` is called
+pub struct GlyphAtlas {
+	pub font: Font,
+	/// The size glyphs are drawn at
+	pub size: f32,
+	glyphs: HashMap<(char, u32), (Metrics, Vec<u8>)>,
+}
+
+impl GlyphAtlas {
+	/// Creates a new empty `GlyphAtlas` drawing `font` at `size`
+	pub fn new(font: Font, size: f32) -> Self {
+		GlyphAtlas {font, size, glyphs: HashMap::new()}
+	}
+	/// Gets the cached glyph of `c` at the current size, rasterizing it first if needed
+	fn glyph(&mut self, c: char) -> &(Metrics, Vec<u8>) {
+		let font = &self.font;
+		let size = self.size;
+		self.glyphs.entry((c, size.to_bits())).or_insert_with(|| font.rasterize(c, size))
+	}
+	/// Gets the number of cached glyphs
+	pub fn len(&self) -> usize {
+		self.glyphs.len()
+	}
+	/// Checks if no glyphs are cached
+	pub fn is_empty(&self) -> bool {
+		self.glyphs.is_empty()
+	}
+	/// Drops all cached glyphs
+	pub fn clear(&mut self) {
+		self.glyphs.clear();
+	}
+}
+
+/// A line of text scrolling right to left through a `Window`, looping seamlessly like a news ticker
+pub struct Marquee {
+	pub text: String,
+	pub font: Font,
+	pub size: f32,
+	/// The space in pixels between the end of the text and its next repetition
+	pub gap: usize,
+	offset: usize,
+}
+
+impl Marquee {
+	/// Creates a new `Marquee` scrolling `text` drawn with `font` at `size`, with a gap of one `size` between repetitions
+	pub fn new(text: &str, font: Font, size: f32) -> Self {
+		Marquee {text: text.to_string(), font, size, gap: size.ceil() as usize, offset: 0}
+	}
+	/// Gets the distance in pixels after which the text repeats
+	fn period(&self) -> usize {
+		measure_text(&self.font, &self.text, self.size).0 + self.gap
+	}
+	/// Scrolls the text `pixels` to the left
+	pub fn tick(&mut self, pixels: usize) {
+		let period = self.period();
+		if period > 0 {
+			self.offset = (self.offset + pixels) % period;
+		}
+	}
+	/// Draws the text at its current offset, vertically centered in the `Window` with the given `id`, in the given color
+	///
+	/// The text is blended over the current contents, and by the global alpha if set, so `fill` the `Window` first to clear the previous position.
+	/// Frozen `Window`s are skipped
+	pub fn render(&self, fm: &mut FBmanager, id: WindowId, rgb: (u8,u8,u8)) {
+		if fm.is_window_frozen(id) {
+			return;
+		}
+		let period = self.period() as isize;
+		if period == 0 {
+			return;
+		}
+		let context = fm.windows[id].main_context.rect();
+		let (_, height) = measure_text(&self.font, &self.text, self.size);
+		let y = (context.height as isize - height as isize) / 2;
+		fm.draw_blended(id, |window, buffer| {
+			// the copy starting at -offset plus as many repetitions as are needed to reach the right edge
+			let mut x = -(self.offset as isize);
+			while x < context.width as isize {
+				window.draw_text_at(buffer, x, y, &self.text, &self.font, self.size, rgb);
+				x += period;
+			}
+		});
+		fm.mark_dirty(context);
+	}
+}
+
+impl Window {
+	/// Draws `text` with `font` at `size` in the given color. `origin` is the window-relative top left corner of the text
+	///
+	/// Text outside of the `main_context` is clipped
+	pub fn draw_text(&self, buffer: &mut [u8], origin: Point, text: &str, font: &Font, size: f32, rgb: (u8,u8,u8)) {
+		self.draw_text_at(buffer, origin.x as isize, origin.y as isize, text, font, size, rgb);
+	}
+	/// Draws `text` like `draw_text` with its top left corner at window-relative `(x, y)`, which may be outside of the `main_context`
+	#[allow(clippy::too_many_arguments)]
+	fn draw_text_at(&self, buffer: &mut [u8], x: isize, y: isize, text: &str, font: &Font, size: f32, rgb: (u8,u8,u8)) {
+		let ascent = match font.horizontal_line_metrics(size) {
+			Some(lm) => lm.ascent,
+			None => size,
+		};
+		let baseline = y as f32 + ascent;
+		let mut pen = x as f32;
+		for c in text.chars() {
+			let (metrics, mask) = font.rasterize(c, size);
+			let x = pen.round() as isize + metrics.xmin as isize;
+			let y = (baseline - metrics.ymin as f32).round() as isize - metrics.height as isize;
+			self.blit_mask(buffer, x, y, metrics.width, &mask, rgb);
+			pen += metrics.advance_width;
+		}
+	}
+	/// Draws `text` like `draw_text` with the font and size of `atlas`, reusing its cached glyphs
+	pub fn draw_text_atlas(&self, buffer: &mut [u8], atlas: &mut GlyphAtlas, origin: Point, text: &str, rgb: (u8,u8,u8)) {
+		let ascent = match atlas.font.horizontal_line_metrics(atlas.size) {
+			Some(lm) => lm.ascent,
+			None => atlas.size,
+		};
+		let baseline = origin.y as f32 + ascent;
+		let mut pen = origin.x as f32;
+		for c in text.chars() {
+			let (metrics, mask) = atlas.glyph(c);
+			let x = pen.round() as isize + metrics.xmin as isize;
+			let y = (baseline - metrics.ymin as f32).round() as isize - metrics.height as isize;
+			self.blit_mask(buffer, x, y, metrics.width, mask, rgb);
+			pen += metrics.advance_width;
+		}
+	}
+	/// Draws `text` at the window-relative top left corner with the largest size up to `max_size` that fits the `main_context`
+	///
+	/// Returns the chosen size, or `None` if the text doesn't fit at any size
+	pub fn draw_text_autofit(&self, buffer: &mut [u8], text: &str, font: &Font, max_size: f32, rgb: (u8,u8,u8)) -> Option<f32> {
+		let fits = |size: usize| {
+			let (w, h) = measure_text(font, text, size as f32);
+			w <= self.main_context.width && h <= self.main_context.height
+		};
+		// binary search for the largest fitting whole pixel size
+		let (mut low, mut high) = (0, max_size.floor() as usize);
+		while low < high {
+			let mid = (low + high).div_ceil(2);
+			if fits(mid) {
+				low = mid;
+			} else {
+				high = mid - 1;
+			}
+		}
+		if low == 0 {
+			return None;
+		}
+		let size = low as f32;
+		self.draw_text(buffer, Point::new(0,0), text, font, size, rgb);
+		Some(size)
+	}
+	/// Draws chart axes meeting at the window-relative `origin`: an `x_len` long axis to the right and a `y_len` long axis upwards
+	///
+	/// Each axis is split into `ticks` even steps over its `(start, end)` range of values, with each tick labeled by its value using `font` at `size`.
+	/// Labels get as many decimals as the step between ticks needs. Everything is clipped to the `main_context`
+	#[allow(clippy::too_many_arguments)]
+	pub fn draw_axes(&self, buffer: &mut [u8], origin: Point, x_len: usize, y_len: usize, x_range: (f32, f32), y_range: (f32, f32), ticks: usize, rgb: (u8,u8,u8), font: &Font, size: f32) {
+		self.draw_line(buffer, origin, Point::new(origin.x + x_len, origin.y), rgb);
+		self.draw_line(buffer, origin, Point::new(origin.x, origin.y.saturating_sub(y_len)), rgb);
+		if ticks == 0 {
+			return;
+		}
+		let tick_len = ((size / 4.0).round() as usize).max(2);
+		let x_step = (x_range.1 - x_range.0) / ticks as f32;
+		let y_step = (y_range.1 - y_range.0) / ticks as f32;
+		for i in 0..=ticks {
+			let label = tick_label(x_range.0 + x_step * i as f32, x_step);
+			let (w, _) = measure_text(font, &label, size);
+			let x = origin.x + i * x_len / ticks;
+			self.draw_line(buffer, Point::new(x, origin.y), Point::new(x, origin.y + tick_len), rgb);
+			self.draw_text(buffer, Point::new(x.saturating_sub(w / 2), origin.y + tick_len + 1), &label, font, size, rgb);
+
+			let label = tick_label(y_range.0 + y_step * i as f32, y_step);
+			let (w, h) = measure_text(font, &label, size);
+			let y = origin.y.saturating_sub(i * y_len / ticks);
+			self.draw_line(buffer, Point::new(origin.x.saturating_sub(tick_len), y), Point::new(origin.x, y), rgb);
+			let label_origin = Point::new(origin.x.saturating_sub(tick_len + 1 + w), y.saturating_sub(h / 2));
+			self.draw_text(buffer, label_origin, &label, font, size, rgb);
+		}
+	}
+	/// Blends a coverage `mask` of the given `width` in the given color with its top left corner at window-relative `(x, y)`
+	pub(crate) fn blit_mask(&self, buffer: &mut [u8], x: isize, y: isize, width: usize, mask: &[u8], rgb: (u8,u8,u8)) {
+		if width == 0 {
+			return;
+		}
+		for (row, line) in mask.chunks(width).enumerate() {
+			let py = y + row as isize;
+			if py < 0 || py as usize >= self.main_context.height {
+				continue;
+			}
+			let pixels = &self.main_context.pixels[py as usize];
+			for (col, &coverage) in line.iter().enumerate() {
+				let px = x + col as isize;
+				if coverage == 0 || px < 0 || px as usize >= self.main_context.width {
+					continue;
+				}
+				pixels[px as usize].blend_rgb(buffer, rgb.0, rgb.1, rgb.2, coverage as f32 / 255.0);
+			}
+		}
+	}
+}
+
+/// Formats the value of an axis tick with as many decimals as the `step` between ticks needs, up to 6
+fn tick_label(value: f32, step: f32) -> String {
+	let step = step.abs();
+	// the fewest decimals that show the step exactly
+	let decimals = (0..6).find(|&d| {
+		let scaled = step * 10f32.powi(d);
+		(scaled - scaled.round()).abs() < 1e-3 * scaled.max(1.0)
+	}).unwrap_or(6);
+	// avoid printing "-0"
+	let value = if value.abs() < 0.5 * 10f32.powi(-decimals) { 0.0 } else { value };
+	format!("{:.*}", decimals as usize, value)
+}
+
+//tests
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn tick_labels() {
+		assert_eq!(tick_label(0.0, 20.0), "0");
+		assert_eq!(tick_label(100.0, 20.0), "100");
+		assert_eq!(tick_label(0.25, 0.25), "0.25");
+		assert_eq!(tick_label(1.5, 0.5), "1.5");
+		assert_eq!(tick_label(-0.0001, 0.1), "0.0");
+		assert_eq!(tick_label(3.0, 0.0), "3");
+	}
+}